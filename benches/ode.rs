@@ -3,7 +3,6 @@ extern crate criterion;
 extern crate eom;
 
 use criterion::Criterion;
-use eom::traits::*;
 use eom::*;
 use ndarray::arr1;
 
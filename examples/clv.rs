@@ -1,6 +1,6 @@
 //! Calculate Covariant Lyapunov Vector
 //!
-//! This function consumes much memory since this saves matrices duraing the time evolution.
+//! The backward iteration is done block by block, and thus the memory usage is bounded.
 
 use ndarray::*;
 use std::io::Write;
@@ -12,10 +12,11 @@ fn main() {
     let eom = ode::Lorenz63::default();
     let teo = explicit::RK4::new(eom, dt);
     let duration = 100000;
-    let ts = lyapunov::vectors(teo, arr1(&[1.0, 0.0, 0.0]), 1e-7, duration);
+    let series = lyapunov::Series::new(teo, arr1(&[1.0, 0.0, 0.0]), 1e-7, 3);
+    let ts = lyapunov::CovariantVectors::new(series, duration / 10, duration / 10, 1000);
     let mut l = Array::zeros(3);
     println!("v0v1, v0v2, v1v2");
-    for (_x, v, f) in ts.into_iter().rev() {
+    for (_x, v, f) in ts.take(duration) {
         let v0 = v.axis_iter(Axis(1)).next().unwrap();
         let v1 = v.axis_iter(Axis(1)).nth(1).unwrap();
         let v2 = v.axis_iter(Axis(1)).nth(2).unwrap();
@@ -14,19 +14,18 @@ fn main() {
     x0[4] = c64::new(1.0, 0.0);
     x0[5] = c64::new(1.0, 0.0);
     x0[6] = c64::new(1.0, 0.0);
-    let ts = adaptor::time_series(x0, &mut teo);
-    let end_time = 10_000_000;
+    let end_time = 100.0;
     let interval = 100;
+    let mut ts = adaptor::timed_series(x0, &mut teo)
+        .end(end_time)
+        .stride(interval);
     print!("time");
     for i in 0..27 {
         print!(",r{},c{}", i, i);
     }
     println!();
-    for (t, v) in ts.take(end_time).enumerate() {
-        if t % interval != 0 {
-            continue;
-        }
-        print!("{:e}", dt * t as f64);
+    while let Some((t, v)) = ts.next_view() {
+        print!("{:e}", t);
         for c in v.iter() {
             print!(",{:e},{:e}", c.re, c.im);
         }
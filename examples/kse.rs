@@ -18,10 +18,9 @@ fn main() {
     let x: Array1<c64> = c64::new(0.01, 0.0) * random(n_coef);
     let x = adaptor::iterate(&mut teo, x, 100);
 
-    let ts = adaptor::time_series(x, &mut teo);
-    for (t, v) in ts.take(step).enumerate() {
-        let time = dt * t as f64;
-        print!("{:e},", time);
+    let ts = adaptor::timed_series(x, &mut teo);
+    for (t, v) in ts.take(step) {
+        print!("{:e},", t);
         let u = pair.to_r(v.as_slice().unwrap());
         let nums: Vec<_> = u.iter().map(|x| format!("{:e}", x)).collect();
         println!("{}", nums.join(","));
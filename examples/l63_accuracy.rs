@@ -6,13 +6,14 @@ use eom::*;
 
 fn check_accuracy<Sc>(teo: Sc, init: Array1<f64>, fname: &str)
 where
-    Sc: Scheme<Scalar = f64, Dim = Ix1, Time = f64>,
+    Sc: Scheme<Scalar = f64, Dim = Ix1, Time = f64> + CurrentTime,
 {
-    let acc = adaptor::accuracy(teo, init, 0.01, 1000, 12);
+    let report = adaptor::convergence(teo, init, adaptor::Reference::Richardson, 0.01, 1000, 12);
+    println!("{}: order = {:.3}", fname, report.order);
     let mut f = File::create(fname).unwrap();
-    writeln!(&mut f, "dt,dev").unwrap();
-    for &(dt, dev) in acc.iter() {
-        writeln!(&mut f, "{:.08e},{:.08e}", dt, dev).unwrap();
+    writeln!(&mut f, "dt,err").unwrap();
+    for &(dt, err) in report.errors.iter() {
+        writeln!(&mut f, "{:.08e},{:.08e}", dt, err).unwrap();
     }
 }
 
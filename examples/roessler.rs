@@ -5,10 +5,9 @@ fn main() {
     let dt = 0.01;
     let eom = ode::Roessler::default();
     let mut teo = explicit::RK4::new(eom, dt);
-    let ts = adaptor::time_series(arr1(&[1.0, 0.0, 0.0]), &mut teo);
-    let end_time = 50000;
+    let ts = adaptor::timed_series(arr1(&[1.0, 0.0, 0.0]), &mut teo).end(500.0);
     println!("time,x,y,z");
-    for (t, v) in ts.take(end_time).enumerate() {
-        println!("{},{},{},{}", t as f64 * dt, v[0], v[1], v[2]);
+    for (t, v) in ts {
+        println!("{},{},{},{}", t, v[0], v[1], v[2]);
     }
 }
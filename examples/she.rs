@@ -16,7 +16,7 @@ fn main() {
     let mut teo = adaptor::nstep(teo, interval);
 
     let x: Array1<c64> = c64::new(0.1, 0.0) * random(n_coef);
-    let ts = adaptor::time_series(x, &mut teo);
+    let ts = adaptor::timed_series(x, &mut teo);
 
     // output CSV header
     print!("time");
@@ -25,9 +25,8 @@ fn main() {
     }
     println!();
 
-    for (t, v) in ts.take(step).enumerate() {
-        let time = dt * t as f64;
-        print!("{:e},", time);
+    for (t, v) in ts.take(step) {
+        print!("{:e},", t);
         let u = pair.to_r(v.as_slice().unwrap());
         let nums: Vec<_> = u.iter().map(|x| format!("{:e}", x)).collect();
         println!("{}", nums.join(","));
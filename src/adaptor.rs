@@ -1,13 +1,15 @@
 //! Utilities for adopting to Rust fashon
 
+use super::event::{illinois, Direction};
 use super::traits::*;
 use ndarray::*;
 use ndarray_linalg::*;
-use num_traits::FromPrimitive;
+use num_traits::{Float, FromPrimitive, One, Zero};
+use std::collections::VecDeque;
 
 /// Test time accuracy of equation of motion
 pub fn accuracy<A, D, Sc>(
-    mut teo: Sc,
+    teo: Sc,
     init: Array<A, D>,
     dt_base: A::Real,
     step_base: usize,
@@ -16,26 +18,177 @@ pub fn accuracy<A, D, Sc>(
 where
     A: Scalar + Lapack,
     D: Dimension,
-    Sc: Scheme<Scalar = A, Dim = D, Time = A::Real>,
+    Sc: Scheme<Scalar = A, Dim = D, Time = A::Real> + CurrentTime,
 {
-    let data: Vec<_> = (0..num_scale)
+    let data = refinements(teo, init, dt_base, step_base, num_scale);
+    data.windows(2)
+        .map(|w| {
+            let dt = w[0].0;
+            let dev = (&w[1].1 - &w[0].1).norm();
+            (dt, dev)
+        })
+        .collect()
+}
+
+/// Solutions at `dt_base * step_base` with halving `dt`, all starting from the current time of `teo`
+fn refinements<A, D, Sc>(
+    mut teo: Sc,
+    init: Array<A, D>,
+    dt_base: A::Real,
+    step_base: usize,
+    num_scale: u32,
+) -> Vec<(A::Real, Array<A, D>)>
+where
+    A: Scalar,
+    D: Dimension,
+    Sc: Scheme<Scalar = A, Dim = D, Time = A::Real> + CurrentTime,
+{
+    let t0 = teo.get_time();
+    (0..num_scale)
         .map(|n| {
             let rate = 2_usize.pow(n);
             let dt = dt_base / A::real(rate as f64);
             let t = step_base * rate;
+            teo.set_time(t0);
             teo.set_dt(dt);
             (dt, iterate(&mut teo, init.clone(), t))
         })
-        .collect();
-    data.windows(2)
-        .map(|w| {
-            let dt = w[0].0;
-            let dev = (&w[1].1 - &w[0].1).norm();
-            (dt, dev)
-        })
         .collect()
 }
 
+/// Reference solution used in [convergence]
+#[derive(Debug, Clone)]
+pub enum Reference<A, D: Dimension> {
+    /// Richardson extrapolation of the two finest solutions with the observed order
+    Richardson,
+    /// Exact or highly accurate solution at `dt_base * step_base`
+    Solution(Array<A, D>),
+}
+
+#[cfg_attr(doc, katexit::katexit)]
+/// Result of [convergence]
+#[derive(Debug, Clone)]
+pub struct ConvergenceReport<R> {
+    /// Pairs of the time step and the error from the reference solution
+    pub errors: Vec<(R, R)>,
+    /// Order fitted by the least square method in log-log scale
+    pub order: R,
+    /// Orders observed between successive refinements, $\log_2(e_{\Delta t} / e_{\Delta t/2})$
+    pub local_orders: Vec<R>,
+}
+
+impl<R: Float + std::fmt::Display> ConvergenceReport<R> {
+    /// Panics if the fitted order differs from `expected` by more than `tol`
+    pub fn assert_order(&self, expected: R, tol: R) {
+        assert!(
+            Float::abs(self.order - expected) <= tol,
+            "Observed order {} differs from the expected order {} (tol = {}), local orders = [{}]",
+            self.order,
+            expected,
+            tol,
+            self.local_orders
+                .iter()
+                .map(|o| o.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+}
+
+#[cfg_attr(doc, katexit::katexit)]
+/// Estimate the order of convergence of the scheme
+///
+/// The solutions at $T = \Delta t_0 N$ from the current time of `teo` are calculated with $\Delta t = \Delta t_0 / 2^n$
+/// for $n = 0, \ldots,$ `num_scale - 1`, and their errors are evaluated against `reference`.
+/// For [Reference::Richardson], the order $p$ is first estimated from the deviations of successive solutions,
+/// and the reference is extrapolated as $x_{\Delta t/2} + (x_{\Delta t/2} - x_{\Delta t}) / (2^p - 1)$
+/// using the two finest solutions.
+///
+/// ```rust
+/// use ndarray::arr1;
+/// use eom::*;
+///
+/// let teo = explicit::RK4::new(ode::Lorenz63::default(), 1.0);
+/// let report = adaptor::convergence(
+///     teo,
+///     arr1(&[1.0, 0.0, 0.0]),
+///     adaptor::Reference::Richardson,
+///     0.001,
+///     1000,
+///     4,
+/// );
+/// report.assert_order(4.0, 0.3);
+/// ```
+pub fn convergence<A, D, Sc>(
+    teo: Sc,
+    init: Array<A, D>,
+    reference: Reference<A, D>,
+    dt_base: A::Real,
+    step_base: usize,
+    num_scale: u32,
+) -> ConvergenceReport<A::Real>
+where
+    A: Scalar + Lapack,
+    D: Dimension,
+    Sc: Scheme<Scalar = A, Dim = D, Time = A::Real> + CurrentTime,
+{
+    let data = refinements(teo, init, dt_base, step_base, num_scale);
+    let reference = match reference {
+        Reference::Solution(x) => x,
+        Reference::Richardson => {
+            assert!(
+                num_scale >= 3,
+                "Richardson extrapolation requires num_scale >= 3"
+            );
+            let dev: Vec<_> = data
+                .windows(2)
+                .map(|w| (w[0].0, (&w[1].1 - &w[0].1).norm()))
+                .collect();
+            let p = fit_order(&dev);
+            let n = data.len();
+            let (fine, coarse) = (&data[n - 1].1, &data[n - 2].1);
+            let c = A::Real::one() / (Float::powf(A::real(2.0), p) - A::Real::one());
+            let mut x = fine.clone();
+            Zip::from(&mut x)
+                .and(coarse)
+                .for_each(|x, &y| *x += (*x - y).mul_real(c));
+            x
+        }
+    };
+    let errors: Vec<_> = data
+        .iter()
+        .map(|(dt, x)| (*dt, (x - &reference).norm()))
+        .collect();
+    let local_orders = errors
+        .windows(2)
+        .map(|w| Float::log2(w[0].1 / w[1].1))
+        .collect();
+    ConvergenceReport {
+        order: fit_order(&errors),
+        errors,
+        local_orders,
+    }
+}
+
+/// Slope of the least square fitting of `(dt, err)` in log-log scale
+fn fit_order<R: Float>(errors: &[(R, R)]) -> R {
+    let points: Vec<_> = errors
+        .iter()
+        .filter(|(_, e)| *e > R::zero())
+        .map(|(dt, e)| (dt.ln(), e.ln()))
+        .collect();
+    let n = R::from(points.len()).unwrap();
+    let mx = points.iter().fold(R::zero(), |acc, p| acc + p.0) / n;
+    let my = points.iter().fold(R::zero(), |acc, p| acc + p.1) / n;
+    let sxy = points
+        .iter()
+        .fold(R::zero(), |acc, p| acc + (p.0 - mx) * (p.1 - my));
+    let sxx = points
+        .iter()
+        .fold(R::zero(), |acc, p| acc + (p.0 - mx) * (p.0 - mx));
+    sxy / sxx
+}
+
 /// Iterate equation of motion by `step` at once
 pub fn iterate<S, TEO>(
     teo: &mut TEO,
@@ -80,11 +233,13 @@ where
 /// let mut teo = explicit::RK4::new(eom, dt);
 /// let ts = adaptor::time_series(arr1(&[1.0, 0.0, 0.0]), &mut teo);
 /// let end_time = 10000;
-/// println!("time,x,y,z");
-/// for (t, v) in ts.take(end_time).enumerate() {
-///     println!("{},{},{},{}", t as f64 * dt, v[0], v[1], v[2]);
+/// println!("x,y,z");
+/// for v in ts.take(end_time) {
+///     println!("{},{},{}", v[0], v[1], v[2]);
 /// }
 /// ```
+///
+/// Use [timed_series] to get the time of each state.
 pub fn time_series<S, TEO>(x0: ArrayBase<S, TEO::Dim>, teo: &mut TEO) -> TimeSeries<'_, S, TEO>
 where
     S: DataMut<Elem = TEO::Scalar> + Data + RawDataClone,
@@ -115,6 +270,137 @@ where
     }
 }
 
+/// An iterator generated by [timed_series] yielding pairs of the time and state
+pub struct TimedSeries<'a, TEO: TimeEvolution + CurrentTime> {
+    state: Array<TEO::Scalar, TEO::Dim>,
+    teo: &'a mut TEO,
+    start: Option<TEO::Time>,
+    end: Option<TEO::Time>,
+    stride: usize,
+    /// Number of steps since the last yield. `None` before the first yield.
+    since: Option<usize>,
+    initial: bool,
+    done: bool,
+}
+
+/// Generate an iterator of `(t, x)` where the time is read by [CurrentTime::get_time] after each step
+///
+/// The initial state `x0` at the current time of `teo` is yielded first.
+/// The range of time and decimation are set by [TimedSeries::start], [TimedSeries::end],
+/// and [TimedSeries::stride].
+/// [Iterator::next] clones the state, and [TimedSeries::next_view] can be used to avoid it.
+///
+/// Since the time is read from the scheme, this is also correct for adaptive schemes.
+/// Use [timed] for a time evolution without [CurrentTime].
+///
+/// Example
+/// ---------
+///
+/// ```rust
+/// use ndarray::arr1;
+/// use eom::*;
+///
+/// let eom = ode::Lorenz63::default();
+/// let mut teo = explicit::RK4::new(eom, 0.01);
+/// let ts = adaptor::timed_series(arr1(&[1.0, 0.0, 0.0]), &mut teo)
+///     .start(10.0)
+///     .end(100.0)
+///     .stride(10);
+/// println!("time,x,y,z");
+/// for (t, v) in ts {
+///     println!("{},{},{},{}", t, v[0], v[1], v[2]);
+/// }
+/// ```
+pub fn timed_series<TEO: TimeEvolution + CurrentTime>(
+    x0: Array<TEO::Scalar, TEO::Dim>,
+    teo: &mut TEO,
+) -> TimedSeries<'_, TEO> {
+    TimedSeries {
+        state: x0,
+        teo,
+        start: None,
+        end: None,
+        stride: 1,
+        since: None,
+        initial: true,
+        done: false,
+    }
+}
+
+impl<'a, TEO: TimeEvolution + CurrentTime> TimedSeries<'a, TEO> {
+    /// Set the time of the initial state by [CurrentTime::set_time]
+    pub fn initial_time(self, t0: TEO::Time) -> Self {
+        self.teo.set_time(t0);
+        self
+    }
+
+    /// Skip the states before `start`
+    pub fn start(mut self, start: TEO::Time) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Stop after `end`
+    pub fn end(mut self, end: TEO::Time) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    /// Yield every `stride` steps after the first yielded state
+    pub fn stride(mut self, stride: usize) -> Self {
+        assert!(stride > 0, "stride must be positive");
+        self.stride = stride;
+        self
+    }
+
+    /// Current time
+    pub fn time(&self) -> TEO::Time {
+        self.teo.get_time()
+    }
+
+    /// Same as [Iterator::next], but yields a view of the internal state without clone
+    #[allow(clippy::type_complexity)]
+    pub fn next_view(&mut self) -> Option<(TEO::Time, ArrayView<'_, TEO::Scalar, TEO::Dim>)> {
+        if self.done {
+            return None;
+        }
+        loop {
+            if self.initial {
+                self.initial = false;
+            } else {
+                self.teo.iterate(&mut self.state);
+                if let Some(since) = self.since.as_mut() {
+                    *since += 1;
+                }
+            }
+            let t = self.teo.get_time();
+            // The time accumulated by the scheme is not exact, e.g. 100 steps of 0.01 exceeds 1.0
+            let tol = self.teo.get_dt() * TEO::Time::from_f64(1e-8).unwrap();
+            if self.end.map(|end| t > end + tol).unwrap_or(false) {
+                self.done = true;
+                return None;
+            }
+            if self.start.map(|start| t < start - tol).unwrap_or(false) {
+                continue;
+            }
+            match self.since {
+                Some(since) if since < self.stride => continue,
+                _ => {
+                    self.since = Some(0);
+                    return Some((t, self.state.view()));
+                }
+            }
+        }
+    }
+}
+
+impl<'a, TEO: TimeEvolution + CurrentTime> Iterator for TimedSeries<'a, TEO> {
+    type Item = (TEO::Time, Array<TEO::Scalar, TEO::Dim>);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_view().map(|(t, x)| (t, x.to_owned()))
+    }
+}
+
 /// An N-step iterator generated by [nstep]
 ///
 /// [nstep]: fn.nstep.html
@@ -157,6 +443,16 @@ impl<TEO: TimeEvolution> TimeStep for NStep<TEO> {
     }
 }
 
+impl<TEO: TimeEvolution + CurrentTime> CurrentTime for NStep<TEO> {
+    fn get_time(&self) -> Self::Time {
+        self.teo.get_time()
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.teo.set_time(t);
+    }
+}
+
 impl<TEO: TimeEvolution> TimeEvolution for NStep<TEO> {
     fn iterate<'a, S>(
         &mut self,
@@ -171,3 +467,601 @@ impl<TEO: TimeEvolution> TimeEvolution for NStep<TEO> {
         x
     }
 }
+
+/// Time evolution counting its time, generated by [timed]
+#[derive(Debug, Clone)]
+pub struct Timed<TEO: TimeEvolution> {
+    teo: TEO,
+    t: TEO::Time,
+}
+
+/// Give [CurrentTime] to a time evolution without it, e.g. to use [timed_series]
+///
+/// The time starts from zero, and is advanced by [TimeStep::get_dt] read before each step.
+/// This is exact only for a fixed step size: an adaptive scheme changes its step inside
+/// [TimeEvolution::iterate], and must provide its own [CurrentTime] instead of using [timed].
+///
+/// ```rust
+/// use ndarray::arr1;
+/// use eom::*;
+///
+/// let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+/// let mut teo = adaptor::timed(adaptor::nstep(teo, 10));
+/// let mut x = arr1(&[1.0, 0.0, 0.0]);
+/// teo.iterate(&mut x);
+/// assert!((teo.get_time() - 0.1).abs() < 1e-12);
+/// ```
+pub fn timed<TEO: TimeEvolution>(teo: TEO) -> Timed<TEO> {
+    Timed {
+        teo,
+        t: TEO::Time::zero(),
+    }
+}
+
+impl<TEO: TimeEvolution> ModelSpec for Timed<TEO> {
+    type Scalar = TEO::Scalar;
+    type Dim = TEO::Dim;
+
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.teo.model_size()
+    }
+}
+
+impl<TEO: TimeEvolution> TimeStep for Timed<TEO> {
+    type Time = TEO::Time;
+
+    fn get_dt(&self) -> Self::Time {
+        self.teo.get_dt()
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.teo.set_dt(dt);
+    }
+}
+
+impl<TEO: TimeEvolution> CurrentTime for Timed<TEO> {
+    fn get_time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+}
+
+impl<TEO: TimeEvolution> TimeEvolution for Timed<TEO> {
+    fn iterate<'a, S>(
+        &mut self,
+        x: &'a mut ArrayBase<S, TEO::Dim>,
+    ) -> &'a mut ArrayBase<S, TEO::Dim>
+    where
+        S: DataMut<Elem = TEO::Scalar>,
+    {
+        let dt = self.teo.get_dt();
+        self.teo.iterate(x);
+        self.t += dt;
+        x
+    }
+}
+
+/// Reason of [BlowUp]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Violation<R> {
+    /// The state contains NaN or infinity
+    NonFinite,
+    /// L2-norm of the state exceeds the bound set by [Checked::set_norm_bound]
+    NormBound { norm: R, bound: R },
+}
+
+/// Error of [Checked] time evolution
+#[derive(Debug, Clone)]
+pub struct BlowUp<A: Scalar, D: Dimension> {
+    /// Number of steps successfully iterated before the violation
+    pub step: usize,
+    /// The last state which passes the check
+    pub last: Array<A, D>,
+    pub violation: Violation<A::Real>,
+}
+
+impl<A: Scalar, D: Dimension> std::fmt::Display for BlowUp<A, D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.violation {
+            Violation::NonFinite => write!(f, "Non-finite state at step {}", self.step),
+            Violation::NormBound { norm, bound } => write!(
+                f,
+                "Norm of state {} exceeds {} at step {}",
+                norm, bound, self.step
+            ),
+        }
+    }
+}
+
+impl<A: Scalar, D: Dimension> std::error::Error for BlowUp<A, D> {}
+
+/// Time evolution checking the state after each step, generated by [checked]
+///
+/// [TimeEvolution::iterate] panics if the check fails, and [Checked::try_iterate] returns [BlowUp]
+/// with the last state which passes the check.
+#[derive(Debug, Clone)]
+pub struct Checked<TEO: TimeEvolution> {
+    teo: TEO,
+    bound: Option<<TEO::Scalar as Scalar>::Real>,
+    step: usize,
+}
+
+/// Generate a time evolution which checks non-finite values and the norm bound of the state
+///
+/// ```rust
+/// use ndarray::arr1;
+/// use eom::*;
+///
+/// let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+/// let mut teo = adaptor::checked(teo);
+/// teo.set_norm_bound(1e3);
+/// let mut x = arr1(&[1.0, 0.0, 0.0]);
+/// teo.try_iterate_n(&mut x, 1000).unwrap();
+/// ```
+pub fn checked<TEO: TimeEvolution>(teo: TEO) -> Checked<TEO> {
+    Checked {
+        teo,
+        bound: None,
+        step: 0,
+    }
+}
+
+impl<TEO: TimeEvolution> Checked<TEO> {
+    /// Set the upper bound of L2-norm of the state
+    pub fn set_norm_bound(&mut self, bound: <TEO::Scalar as Scalar>::Real) {
+        self.bound = Some(bound);
+    }
+
+    /// Number of steps iterated since created
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// Get the inner time evolution
+    pub fn into_inner(self) -> TEO {
+        self.teo
+    }
+
+    fn check<S>(
+        &self,
+        x: &ArrayBase<S, TEO::Dim>,
+    ) -> Option<Violation<<TEO::Scalar as Scalar>::Real>>
+    where
+        S: Data<Elem = TEO::Scalar>,
+    {
+        if !x
+            .iter()
+            .all(|x| Float::is_finite(x.re()) && Float::is_finite(x.im()))
+        {
+            return Some(Violation::NonFinite);
+        }
+        if let Some(bound) = self.bound {
+            let norm = Float::sqrt(x.iter().map(|x| x.square()).sum());
+            if norm > bound {
+                return Some(Violation::NormBound { norm, bound });
+            }
+        }
+        None
+    }
+
+    /// Calculate next step, and check the result
+    ///
+    /// `x` is restored to the last state if the check fails.
+    #[allow(clippy::type_complexity)]
+    pub fn try_iterate<'a, S>(
+        &mut self,
+        x: &'a mut ArrayBase<S, TEO::Dim>,
+    ) -> Result<&'a mut ArrayBase<S, TEO::Dim>, BlowUp<TEO::Scalar, TEO::Dim>>
+    where
+        S: DataMut<Elem = TEO::Scalar>,
+    {
+        let last = x.to_owned();
+        self.teo.iterate(x);
+        if let Some(violation) = self.check(x) {
+            x.assign(&last);
+            return Err(BlowUp {
+                step: self.step,
+                last,
+                violation,
+            });
+        }
+        self.step += 1;
+        Ok(x)
+    }
+
+    /// Calculate `n` steps, and check the result of each step
+    #[allow(clippy::type_complexity)]
+    pub fn try_iterate_n<'a, S>(
+        &mut self,
+        x: &'a mut ArrayBase<S, TEO::Dim>,
+        n: usize,
+    ) -> Result<&'a mut ArrayBase<S, TEO::Dim>, BlowUp<TEO::Scalar, TEO::Dim>>
+    where
+        S: DataMut<Elem = TEO::Scalar>,
+    {
+        for _ in 0..n {
+            self.try_iterate(x)?;
+        }
+        Ok(x)
+    }
+}
+
+impl<TEO: TimeEvolution> ModelSpec for Checked<TEO> {
+    type Scalar = TEO::Scalar;
+    type Dim = TEO::Dim;
+
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.teo.model_size()
+    }
+}
+
+impl<TEO: TimeEvolution> TimeStep for Checked<TEO> {
+    type Time = TEO::Time;
+
+    fn get_dt(&self) -> Self::Time {
+        self.teo.get_dt()
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.teo.set_dt(dt);
+    }
+}
+
+impl<TEO: TimeEvolution + CurrentTime> CurrentTime for Checked<TEO> {
+    fn get_time(&self) -> Self::Time {
+        self.teo.get_time()
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.teo.set_time(t);
+    }
+}
+
+impl<TEO: TimeEvolution> TimeEvolution for Checked<TEO> {
+    fn iterate<'a, S>(
+        &mut self,
+        x: &'a mut ArrayBase<S, TEO::Dim>,
+    ) -> &'a mut ArrayBase<S, TEO::Dim>
+    where
+        S: DataMut<Elem = TEO::Scalar>,
+    {
+        match self.try_iterate(x) {
+            Ok(x) => x,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+/// An iterator generated by [checked_time_series]
+pub struct CheckedTimeSeries<'a, TEO: TimeEvolution> {
+    state: Option<Array<TEO::Scalar, TEO::Dim>>,
+    teo: &'a mut Checked<TEO>,
+}
+
+/// Generate an iterator of the time-series checked by [Checked]
+///
+/// Different from [time_series], this yields `Err` once if the check fails, and ends.
+pub fn checked_time_series<TEO: TimeEvolution>(
+    x0: Array<TEO::Scalar, TEO::Dim>,
+    teo: &mut Checked<TEO>,
+) -> CheckedTimeSeries<'_, TEO> {
+    CheckedTimeSeries {
+        state: Some(x0),
+        teo,
+    }
+}
+
+impl<'a, TEO: TimeEvolution> Iterator for CheckedTimeSeries<'a, TEO> {
+    type Item = Result<Array<TEO::Scalar, TEO::Dim>, BlowUp<TEO::Scalar, TEO::Dim>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let state = self.state.as_mut()?;
+        match self.teo.try_iterate(state) {
+            Ok(x) => Some(Ok(x.clone())),
+            Err(e) => {
+                self.state = None;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Intersection of the trajectory and the Poincaré section, yielded by [Poincare]
+#[derive(Debug, Clone)]
+pub struct Intersection<A: Scalar, D: Dimension> {
+    pub t: A::Real,
+    pub x: Array<A, D>,
+    /// Time from the previous intersection. `None` for the first one.
+    pub return_time: Option<A::Real>,
+}
+
+/// An iterator of the intersections with a Poincaré section, generated by [poincare]
+pub struct Poincare<TEO>
+where
+    TEO: TimeEvolution + TimeStep<Time = <TEO::Scalar as Scalar>::Real>,
+{
+    teo: TEO,
+    state: Array<TEO::Scalar, TEO::Dim>,
+    normal: Array<TEO::Scalar, TEO::Dim>,
+    offset: TEO::Time,
+    direction: Direction,
+    /// Last states `(t, x, g)` where `g` is the signed distance from the section
+    #[allow(clippy::type_complexity)]
+    history: VecDeque<(TEO::Time, Array<TEO::Scalar, TEO::Dim>, TEO::Time)>,
+    /// Time counted by the step sizes
+    t: TEO::Time,
+    /// Read the time from `teo` instead of counting, set by [Poincare::current_time]
+    clock: Option<fn(&TEO) -> TEO::Time>,
+    last: Option<TEO::Time>,
+    max_steps: usize,
+    done: bool,
+}
+
+#[cfg_attr(doc, katexit::katexit)]
+/// Generate an iterator of the intersections with the hyperplane $n \cdot x = c$
+///
+/// Only the crossings in `direction` of $n \cdot x - c$ are yielded.
+/// For complex states, the real part of $\sum_i n_i^* x_i$ is used.
+/// The intersections are located on the cubic interpolation of the last four states,
+/// which requires neither the right hand side nor a fixed step size.
+/// Until four states are stored, i.e. for a crossing in the first two steps,
+/// the interpolation falls back to the linear or quadratic one of the stored states.
+///
+/// The time starts from zero, and is advanced by [TimeStep::get_dt] read before each step.
+/// An adaptive scheme changes its step inside [TimeEvolution::iterate],
+/// and [Poincare::current_time] must be used to read its time by [CurrentTime] instead.
+///
+/// The iterator ends if no intersection is found in [Poincare::max_steps] steps
+/// or the state becomes non-finite.
+///
+/// ```rust
+/// use ndarray::arr1;
+/// use eom::{*, event::Direction};
+///
+/// let teo = explicit::RK4::new(ode::Roessler::default(), 0.01);
+/// let section = adaptor::poincare(
+///     teo,
+///     arr1(&[1.0, 0.0, 0.0]),
+///     arr1(&[1.0, 0.0, 0.0]),
+///     0.0,
+///     Direction::Increasing,
+/// );
+/// for p in section.skip(1).take(10) {
+///     println!("{}, {}, {}", p.x[1], p.x[2], p.return_time.unwrap());
+/// }
+/// ```
+pub fn poincare<TEO>(
+    teo: TEO,
+    x0: Array<TEO::Scalar, TEO::Dim>,
+    normal: Array<TEO::Scalar, TEO::Dim>,
+    offset: TEO::Time,
+    direction: Direction,
+) -> Poincare<TEO>
+where
+    TEO: TimeEvolution + TimeStep<Time = <TEO::Scalar as Scalar>::Real>,
+{
+    let mut section = Poincare {
+        teo,
+        state: x0,
+        normal,
+        offset,
+        direction,
+        history: VecDeque::with_capacity(4),
+        t: TEO::Time::zero(),
+        clock: None,
+        last: None,
+        max_steps: 100_000,
+        done: false,
+    };
+    let g = section.distance(&section.state);
+    section
+        .history
+        .push_back((section.t, section.state.clone(), g));
+    section
+}
+
+impl<TEO> Poincare<TEO>
+where
+    TEO: TimeEvolution + TimeStep<Time = <TEO::Scalar as Scalar>::Real>,
+{
+    /// Stop if no intersection is found in `max_steps` steps (default: 100000)
+    pub fn max_steps(mut self, max_steps: usize) -> Self {
+        assert!(max_steps > 0, "max_steps must be positive");
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Read the time by [CurrentTime::get_time] of `teo` instead of counting the step sizes
+    ///
+    /// This is required for adaptive schemes, and must be called before iterating.
+    pub fn current_time(mut self) -> Self
+    where
+        TEO: CurrentTime,
+    {
+        assert!(
+            self.history.len() == 1,
+            "current_time must be set before iterating"
+        );
+        let t = self.teo.get_time();
+        self.t = t;
+        self.history[0].0 = t;
+        self.clock = Some(|teo: &TEO| teo.get_time());
+        self
+    }
+
+    /// Current state of the time evolution, which may be ahead of the last intersection
+    pub fn state(&self) -> &Array<TEO::Scalar, TEO::Dim> {
+        &self.state
+    }
+
+    fn distance(&self, x: &Array<TEO::Scalar, TEO::Dim>) -> TEO::Time {
+        let s = Zip::from(&self.normal)
+            .and(x)
+            .fold(TEO::Scalar::zero(), |acc, n, x| acc + n.conj() * *x);
+        s.re() - self.offset
+    }
+
+    /// Lagrange basis of the stored times at `t`, of degree less than four while the history is filled
+    fn weights(&self, t: TEO::Time) -> Vec<TEO::Time> {
+        self.history
+            .iter()
+            .enumerate()
+            .map(|(j, (tj, _, _))| {
+                self.history
+                    .iter()
+                    .enumerate()
+                    .filter(|&(m, _)| m != j)
+                    .fold(TEO::Time::one(), |w, (_, (tm, _, _))| {
+                        w * (t - *tm) / (*tj - *tm)
+                    })
+            })
+            .collect()
+    }
+
+    /// Locate the intersection in the last step
+    fn intersection(&self) -> (TEO::Time, Array<TEO::Scalar, TEO::Dim>) {
+        let n = self.history.len();
+        let (t0, _, g0) = self.history[n - 2];
+        let (t1, _, g1) = self.history[n - 1];
+        let h = t1 - t0;
+        let theta = illinois(
+            |theta| {
+                self.weights(t0 + theta * h)
+                    .iter()
+                    .zip(&self.history)
+                    .fold(TEO::Time::zero(), |acc, (w, (_, _, g))| acc + *w * *g)
+            },
+            g0,
+            g1,
+            TEO::Time::from_f64(1e-12).unwrap() / Float::abs(h),
+            100,
+        );
+        let t = t0 + theta * h;
+        let mut x = Array::zeros(self.state.raw_dim());
+        for (w, (_, xj, _)) in self.weights(t).iter().zip(&self.history) {
+            Zip::from(&mut x)
+                .and(xj)
+                .for_each(|x, xj| *x += xj.mul_real(*w));
+        }
+        (t, x)
+    }
+}
+
+impl<TEO> Iterator for Poincare<TEO>
+where
+    TEO: TimeEvolution + TimeStep<Time = <TEO::Scalar as Scalar>::Real>,
+{
+    type Item = Intersection<TEO::Scalar, TEO::Dim>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        for _ in 0..self.max_steps {
+            let dt = self.teo.get_dt();
+            self.teo.iterate(&mut self.state);
+            self.t = match self.clock {
+                Some(clock) => clock(&self.teo),
+                None => self.t + dt,
+            };
+            let t = self.t;
+            let g = self.distance(&self.state);
+            if !g.is_finite() {
+                break;
+            }
+            // reuse the buffer of the oldest state
+            let entry = if self.history.len() == 4 {
+                let (_, mut x, _) = self.history.pop_front().unwrap();
+                x.assign(&self.state);
+                (t, x, g)
+            } else {
+                (t, self.state.clone(), g)
+            };
+            self.history.push_back(entry);
+            let g0 = self.history[self.history.len() - 2].2;
+            if self.direction.is_crossing(g0, g) {
+                let (t, x) = self.intersection();
+                let return_time = self.last.map(|last| t - last);
+                self.last = Some(t);
+                return Some(Intersection { t, x, return_time });
+            }
+        }
+        self.done = true;
+        None
+    }
+}
+
+/// An iterator generated by [sample]
+pub struct Sample<'a, TEO: DenseOutput, I> {
+    state: Array<TEO::Scalar, TEO::Dim>,
+    teo: &'a mut TEO,
+    times: I,
+    stepped: bool,
+}
+
+/// Generate an iterator of the states at the given times using the dense output
+///
+/// `x0` is the state at the current time of `teo`, and `times` must be non-decreasing
+/// and not before the current time. The dense output of `teo` is enabled by this function.
+///
+/// ```rust
+/// use ndarray::arr1;
+/// use eom::*;
+///
+/// let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+/// let times = (0..100).map(|i| i as f64 * 0.123);
+/// for x in adaptor::sample(arr1(&[1.0, 0.0, 0.0]), &mut teo, times) {
+///     println!("{},{},{}", x[0], x[1], x[2]);
+/// }
+/// ```
+pub fn sample<TEO, I>(
+    x0: Array<TEO::Scalar, TEO::Dim>,
+    teo: &mut TEO,
+    times: I,
+) -> Sample<'_, TEO, I::IntoIter>
+where
+    TEO: DenseOutput,
+    I: IntoIterator<Item = TEO::Time>,
+{
+    teo.set_dense_output(true);
+    Sample {
+        state: x0,
+        teo,
+        times: times.into_iter(),
+        stepped: false,
+    }
+}
+
+impl<'a, TEO, I> Iterator for Sample<'a, TEO, I>
+where
+    TEO: DenseOutput,
+    I: Iterator<Item = TEO::Time>,
+{
+    type Item = Array<TEO::Scalar, TEO::Dim>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let t = self.times.next()?;
+        while self.teo.get_time() < t {
+            self.teo.iterate(&mut self.state);
+            self.stepped = true;
+        }
+        let now = self.teo.get_time();
+        if t == now {
+            return Some(self.state.clone());
+        }
+        assert!(
+            self.stepped,
+            "Sampling time {} is before the initial time",
+            t
+        );
+        let dt = self.teo.last_dt();
+        let theta = (t - (now - dt)) / dt;
+        assert!(
+            theta >= TEO::Time::zero(),
+            "Sampling times must be non-decreasing"
+        );
+        let mut x = self.state.clone();
+        self.teo.dense_output(theta, &mut x);
+        Some(x)
+    }
+}
@@ -0,0 +1,18 @@
+//! Helpers for the scalar types which may be real or complex
+
+use ndarray_linalg::Scalar;
+
+/// Imaginary unit if `A` is complex, `None` if `A` is real
+///
+/// [Scalar] does not tell whether the type is complex,
+/// but $\sqrt{-1}$ distinguishes them: it is $i$ for the complex types,
+/// and NaN for the real types, whose imaginary part is always zero.
+#[cfg_attr(doc, katexit::katexit)]
+pub(crate) fn imaginary_unit<A: Scalar>() -> Option<A> {
+    let i = A::from_real(A::real(-1.0)).sqrt();
+    if i.im() == A::real(1.0) {
+        Some(i)
+    } else {
+        None
+    }
+}
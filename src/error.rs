@@ -0,0 +1,67 @@
+//! Error type of fallible calculations
+
+use std::fmt;
+
+/// Error of fallible calculations, e.g. [lyapunov::Series::try_next] or [pde::Pair::try_new]
+///
+/// [lyapunov::Series::try_next]: crate::lyapunov::Series::try_next
+/// [pde::Pair::try_new]: crate::pde::Pair::try_new
+#[derive(Debug)]
+pub enum Error {
+    /// Failure of linear algebra, e.g. QR decomposition of a degenerated tangent space
+    Linalg(ndarray_linalg::error::LinalgError),
+    /// Failure of FFTW, e.g. plan creation
+    FFTW(fftw::error::Error),
+    /// State or tangent vectors become NaN or infinity
+    NonFinite,
+    /// Step size of an adaptive scheme shrinks to its lower limit without an accepted step,
+    /// e.g. in [explicit::DOP853::try_iterate]
+    ///
+    /// [explicit::DOP853::try_iterate]: crate::explicit::DOP853::try_iterate
+    StepUnderflow,
+    /// Iteration does not converge in the given steps, e.g. [lyapunov::converged_exponents]
+    ///
+    /// `stderr` is the largest standard error at the last step if available.
+    ///
+    /// [lyapunov::converged_exponents]: crate::lyapunov::converged_exponents
+    NotConverged { stderr: Option<f64> },
+}
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Linalg(e) => write!(f, "Linear algebra error: {}", e),
+            Error::FFTW(e) => write!(f, "FFTW error: {}", e),
+            Error::NonFinite => write!(f, "Non-finite value appears"),
+            Error::StepUnderflow => write!(f, "Step size underflow"),
+            Error::NotConverged { stderr: Some(e) } => {
+                write!(f, "Not converged (standard error = {})", e)
+            }
+            Error::NotConverged { stderr: None } => write!(f, "Not converged"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Linalg(e) => Some(e),
+            Error::FFTW(e) => Some(e),
+            Error::NonFinite | Error::StepUnderflow | Error::NotConverged { .. } => None,
+        }
+    }
+}
+
+impl From<ndarray_linalg::error::LinalgError> for Error {
+    fn from(e: ndarray_linalg::error::LinalgError) -> Self {
+        Error::Linalg(e)
+    }
+}
+
+impl From<fftw::error::Error> for Error {
+    fn from(e: fftw::error::Error) -> Self {
+        Error::FFTW(e)
+    }
+}
@@ -0,0 +1,312 @@
+//! Event detection during time evolution
+
+use ndarray::*;
+use ndarray_linalg::*;
+use num_traits::Float;
+
+use crate::{hermite::Hermite, traits::*};
+
+/// Direction of the zero-crossing of an event function to be detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Detect both crossings
+    Both,
+    /// Detect crossings from negative to positive
+    Increasing,
+    /// Detect crossings from positive to negative
+    Decreasing,
+}
+
+impl Direction {
+    /// Whether the function changes its sign from `g0` to `g1` in this direction
+    pub(crate) fn is_crossing<R: Float>(self, g0: R, g1: R) -> bool {
+        let zero = R::zero();
+        let increasing = g0 < zero && g1 >= zero;
+        let decreasing = g0 > zero && g1 <= zero;
+        match self {
+            Direction::Both => increasing || decreasing,
+            Direction::Increasing => increasing,
+            Direction::Decreasing => decreasing,
+        }
+    }
+}
+
+type EventFn<A, D> = Box<dyn FnMut(<A as Scalar>::Real, &Array<A, D>) -> <A as Scalar>::Real>;
+
+#[cfg_attr(doc, katexit::katexit)]
+/// Event defined by the zero of a function $g(t, x)$
+pub struct Event<A: Scalar, D: Dimension> {
+    g: EventFn<A, D>,
+    direction: Direction,
+    terminal: bool,
+}
+
+impl<A: Scalar, D: Dimension> Event<A, D> {
+    /// Non-terminal event detecting both directions
+    pub fn new<G>(g: G) -> Self
+    where
+        G: FnMut(A::Real, &Array<A, D>) -> A::Real + 'static,
+    {
+        Event {
+            g: Box::new(g),
+            direction: Direction::Both,
+            terminal: false,
+        }
+    }
+
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+    }
+
+    /// Stop the time evolution at the first occurrence of this event
+    pub fn set_terminal(&mut self, terminal: bool) {
+        self.terminal = terminal;
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub fn terminal(&self) -> bool {
+        self.terminal
+    }
+}
+
+/// Occurrence of an [Event]
+#[derive(Debug, Clone)]
+pub struct Root<A: Scalar, D: Dimension> {
+    /// Index of the event in the order of [Detector::add_event]
+    pub event: usize,
+    pub t: A::Real,
+    pub x: Array<A, D>,
+}
+
+/// Time series and events generated by [Detector::run]
+#[derive(Debug, Clone)]
+pub struct Trajectory<A: Scalar, D: Dimension> {
+    /// Time of each state in `x`
+    pub t: Vec<A::Real>,
+    pub x: Vec<Array<A, D>>,
+    /// Events in time order
+    pub events: Vec<Root<A, D>>,
+    /// Whether the time evolution is stopped by a terminal event
+    pub terminated: bool,
+}
+
+#[cfg_attr(doc, katexit::katexit)]
+/// Time evolution with event detection
+///
+/// Event functions $g(t, x)$ are evaluated at the end of each step,
+/// and the zero-crossings are located by the Illinois method
+/// on the cubic Hermite interpolation of the step
+/// $$
+/// x(t_0 + \theta h) = h_{00}(\theta) x_0 + h_{10}(\theta) h f_0 + h_{01}(\theta) x_1 + h_{11}(\theta) h f_1
+/// $$
+/// where $f_i = f(t_i, x_i)$ is evaluated by the core of the scheme.
+///
+/// ```rust
+/// use ndarray::arr1;
+/// use eom::{*, event::*};
+///
+/// let teo = explicit::RK4::new(ode::Roessler::default(), 0.01);
+/// let mut detector = Detector::new(teo);
+/// let mut event = Event::new(|_t, x| x[0]);
+/// event.set_direction(Direction::Increasing);
+/// event.set_terminal(true);
+/// detector.add_event(event);
+/// let traj = detector.run(arr1(&[1.0, 0.0, 0.0]), 10000);
+/// assert!(traj.terminated);
+/// assert!(traj.events[0].x[0].abs() < 1e-8);
+/// ```
+pub struct Detector<TEO>
+where
+    TEO: Scheme + CurrentTime<Time = <TEO::Scalar as Scalar>::Real>,
+    TEO::Core: NonAutonomousExplicit,
+{
+    teo: TEO,
+    events: Vec<Event<TEO::Scalar, TEO::Dim>>,
+    tol: <TEO::Scalar as Scalar>::Real,
+    max_iter: usize,
+    /// Time rewound by the last terminal event
+    restart: Option<<TEO::Scalar as Scalar>::Real>,
+}
+
+impl<A, TEO> Detector<TEO>
+where
+    A: Scalar,
+    TEO: Scheme<Scalar = A> + CurrentTime<Time = A::Real>,
+    TEO::Core: NonAutonomousExplicit,
+{
+    pub fn new(teo: TEO) -> Self {
+        Detector {
+            teo,
+            events: Vec::new(),
+            tol: A::real(1e-12),
+            max_iter: 100,
+            restart: None,
+        }
+    }
+
+    /// Register an event. Its index is used in [Root::event]
+    pub fn add_event(&mut self, event: Event<A, TEO::Dim>) -> usize {
+        self.events.push(event);
+        self.events.len() - 1
+    }
+
+    /// Set the absolute tolerance of the event time (default: 1e-12)
+    pub fn set_tolerance(&mut self, tol: A::Real) {
+        self.tol = tol;
+    }
+
+    pub fn teo(&self) -> &TEO {
+        &self.teo
+    }
+
+    pub fn teo_mut(&mut self) -> &mut TEO {
+        &mut self.teo
+    }
+
+    fn rhs(&mut self, t: A::Real, x: &Array<A, TEO::Dim>) -> Array<A, TEO::Dim> {
+        let mut f = x.clone();
+        self.teo.core_mut().rhs_t(t, &mut f);
+        f
+    }
+
+    /// Iterate a step and detect the events in it
+    ///
+    /// If a terminal event occurs, `x` and the current time are rewound to the first terminal event,
+    /// and the events after it are discarded.
+    /// When the evolution is resumed from there, the events within the tolerance of the restart time
+    /// are ignored since the event functions are almost zero at that point.
+    pub fn iterate<S>(&mut self, x: &mut ArrayBase<S, TEO::Dim>) -> Vec<Root<A, TEO::Dim>>
+    where
+        S: DataMut<Elem = A>,
+    {
+        let t0 = self.teo.get_time();
+        let restarted = self.restart.take() == Some(t0);
+        let x0 = x.to_owned();
+        let g0: Vec<_> = self.events.iter_mut().map(|e| (e.g)(t0, &x0)).collect();
+        self.teo.iterate(x);
+        let t1 = self.teo.get_time();
+        let x1 = x.to_owned();
+        let g1: Vec<_> = self.events.iter_mut().map(|e| (e.g)(t1, &x1)).collect();
+
+        let crossing: Vec<_> = (0..self.events.len())
+            .filter(|&i| self.events[i].direction.is_crossing(g0[i], g1[i]))
+            .collect();
+        if crossing.is_empty() {
+            return Vec::new();
+        }
+
+        let f0 = self.rhs(t0, &x0);
+        let h = t1 - t0;
+        let mut dense = Hermite::new(x0.raw_dim());
+        dense.update(self.teo.core_mut(), t1, h, &x0, Some(&f0), &x1);
+        let interp = |theta: A::Real| {
+            let mut x = x0.clone();
+            dense.eval(theta, &mut x);
+            x
+        };
+        let tol = self.tol;
+
+        let mut roots: Vec<_> = crossing
+            .into_iter()
+            .map(|i| {
+                let g = &mut self.events[i].g;
+                let theta = illinois(
+                    |theta| g(t0 + theta * h, &interp(theta)),
+                    g0[i],
+                    g1[i],
+                    tol / Float::abs(h),
+                    self.max_iter,
+                );
+                Root {
+                    event: i,
+                    t: t0 + theta * h,
+                    x: interp(theta),
+                }
+            })
+            .filter(|root| !(restarted && Float::abs(root.t - t0) <= tol))
+            .collect();
+        // NaN is placed last
+        roots.sort_by(|a, b| {
+            a.t.partial_cmp(&b.t)
+                .unwrap_or_else(|| a.t.is_nan().cmp(&b.t.is_nan()))
+        });
+
+        if let Some(n) = roots.iter().position(|r| self.events[r.event].terminal) {
+            roots.truncate(n + 1);
+            let root = &roots[n];
+            x.assign(&root.x);
+            self.teo.set_time(root.t);
+            self.restart = Some(root.t);
+        }
+        roots
+    }
+
+    /// Iterate at most `n` steps, and stop at the first terminal event
+    pub fn run(&mut self, mut x: Array<A, TEO::Dim>, n: usize) -> Trajectory<A, TEO::Dim> {
+        let mut traj = Trajectory {
+            t: vec![self.teo.get_time()],
+            x: vec![x.clone()],
+            events: Vec::new(),
+            terminated: false,
+        };
+        for _ in 0..n {
+            let roots = self.iterate(&mut x);
+            traj.terminated = roots
+                .last()
+                .map(|r| self.events[r.event].terminal)
+                .unwrap_or(false);
+            traj.events.extend(roots);
+            traj.t.push(self.teo.get_time());
+            traj.x.push(x.clone());
+            if traj.terminated {
+                break;
+            }
+        }
+        traj
+    }
+}
+
+/// Find the root of `g` in `[0, 1]` by the Illinois method
+pub(crate) fn illinois<R, G>(mut g: G, g0: R, g1: R, tol: R, max_iter: usize) -> R
+where
+    R: Float,
+    G: FnMut(R) -> R,
+{
+    let (mut a, mut ga) = (R::zero(), g0);
+    let (mut b, mut gb) = (R::one(), g1);
+    if gb == R::zero() {
+        return b;
+    }
+    let half = R::from(0.5).unwrap();
+    // which side was retained in the last iteration: -1 for `a`, 1 for `b`
+    let mut side = 0;
+    for _ in 0..max_iter {
+        if b - a < tol {
+            break;
+        }
+        let c = (a * gb - b * ga) / (gb - ga);
+        let gc = g(c);
+        if gc == R::zero() {
+            return c;
+        }
+        if (gc > R::zero()) == (gb > R::zero()) {
+            b = c;
+            gb = gc;
+            if side == -1 {
+                ga = ga * half;
+            }
+            side = -1;
+        } else {
+            a = c;
+            ga = gc;
+            if side == 1 {
+                gb = gb * half;
+            }
+            side = 1;
+        }
+    }
+    (a * gb - b * ga) / (gb - ga)
+}
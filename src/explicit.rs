@@ -1,17 +1,24 @@
 //! explicit schemes
 
+use super::error::{Error, Result};
+use super::hermite::Hermite;
 use super::traits::*;
 use ndarray::*;
 use ndarray_linalg::*;
+use num_traits::{Float, Zero};
 
 #[derive(Debug, Clone)]
-pub struct Euler<F: Explicit> {
+pub struct Euler<F: NonAutonomousExplicit> {
     f: F,
+    t: <F::Scalar as Scalar>::Real,
     dt: <F::Scalar as Scalar>::Real,
+    last_dt: <F::Scalar as Scalar>::Real,
     x: Array<F::Scalar, F::Dim>,
+    dense: Option<Hermite<F::Scalar, F::Dim>>,
+    tangent: Option<TangentRK<F::Scalar, F::Dim>>,
 }
 
-impl<A: Scalar, F: Explicit<Scalar = A>> TimeStep for Euler<F> {
+impl<A: Scalar, F: NonAutonomousExplicit<Scalar = A>> TimeStep for Euler<F> {
     type Time = A::Real;
 
     fn get_dt(&self) -> Self::Time {
@@ -23,11 +30,30 @@ impl<A: Scalar, F: Explicit<Scalar = A>> TimeStep for Euler<F> {
     }
 }
 
-impl<F: Explicit> Scheme for Euler<F> {
+impl<A: Scalar, F: NonAutonomousExplicit<Scalar = A>> CurrentTime for Euler<F> {
+    fn get_time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+}
+
+impl<F: NonAutonomousExplicit> Scheme for Euler<F> {
     type Core = F;
     fn new(f: F, dt: Self::Time) -> Self {
         let x = Array::zeros(f.model_size());
-        Self { f, dt, x }
+        let t = F::Scalar::real(0.0);
+        Self {
+            f,
+            t,
+            dt,
+            last_dt: dt,
+            x,
+            dense: None,
+            tangent: None,
+        }
     }
     fn core(&self) -> &Self::Core {
         &self.f
@@ -37,7 +63,7 @@ impl<F: Explicit> Scheme for Euler<F> {
     }
 }
 
-impl<F: Explicit> ModelSpec for Euler<F> {
+impl<F: NonAutonomousExplicit> ModelSpec for Euler<F> {
     type Scalar = F::Scalar;
     type Dim = F::Dim;
     fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
@@ -45,29 +71,96 @@ impl<F: Explicit> ModelSpec for Euler<F> {
     }
 }
 
-impl<F: Explicit> TimeEvolution for Euler<F> {
+impl<F: NonAutonomousExplicit> TimeEvolution for Euler<F> {
     fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, F::Dim>) -> &'a mut ArrayBase<S, Self::Dim>
     where
         S: DataMut<Elem = Self::Scalar>,
     {
         self.x.zip_mut_with(x, |buf, x| *buf = *x);
-        let fx = self.f.rhs(x);
+        let fx = self.f.rhs_t(self.t, x);
         Zip::from(&mut *fx).and(&self.x).for_each(|vfx, vx| {
             *vfx = *vx + vfx.mul_real(self.dt);
         });
+        self.t += self.dt;
+        self.last_dt = self.dt;
+        if let Some(dense) = &mut self.dense {
+            dense.update(&mut self.f, self.t, self.dt, &self.x, None, fx);
+        }
         fx
     }
 }
 
+impl<F: NonAutonomousExplicit> DenseOutput for Euler<F> {
+    fn set_dense_output(&mut self, dense: bool) {
+        self.dense = if dense {
+            Some(Hermite::new(self.f.model_size()))
+        } else {
+            None
+        };
+    }
+
+    fn last_dt(&self) -> Self::Time {
+        self.last_dt
+    }
+
+    fn dense_output<'a, S>(
+        &self,
+        theta: Self::Time,
+        x: &'a mut ArrayBase<S, F::Dim>,
+    ) -> &'a mut ArrayBase<S, F::Dim>
+    where
+        S: DataMut<Elem = F::Scalar>,
+    {
+        self.dense
+            .as_ref()
+            .expect("Dense output of Euler is disabled")
+            .eval(theta, x)
+    }
+}
+
+impl<F: AnalyticJacobian> TangentEvolution for Euler<F> {
+    fn iterate_tangent<'a, S, St>(
+        &mut self,
+        x: &'a mut ArrayBase<S, F::Dim>,
+        dx: &mut ArrayBase<St, <F::Dim as Dimension>::Larger>,
+    ) -> &'a mut ArrayBase<S, F::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+        St: DataMut<Elem = Self::Scalar>,
+        <F::Dim as Dimension>::Larger: RemoveAxis + Dimension<Smaller = F::Dim>,
+    {
+        let size = self.f.model_size();
+        let tangent = self.tangent.get_or_insert_with(|| TangentRK::new(size, 1));
+        tangent.step(&mut self.f, self.t, x, dx, &[&[]], &[1.0], self.dt);
+        self.t += self.dt;
+        self.last_dt = self.dt;
+        if let Some(dense) = &mut self.dense {
+            dense.update(
+                &mut self.f,
+                self.t,
+                self.dt,
+                &tangent.y[0],
+                Some(&tangent.k[0]),
+                x,
+            );
+        }
+        x
+    }
+}
+
 #[derive(Debug, Clone)]
-pub struct Heun<F: Explicit> {
+pub struct Heun<F: NonAutonomousExplicit> {
     f: F,
+    t: <F::Scalar as Scalar>::Real,
     dt: <F::Scalar as Scalar>::Real,
+    last_dt: <F::Scalar as Scalar>::Real,
     x: Array<F::Scalar, F::Dim>,
     k1: Array<F::Scalar, F::Dim>,
+    dense: Option<Hermite<F::Scalar, F::Dim>>,
+    tangent: Option<TangentRK<F::Scalar, F::Dim>>,
 }
 
-impl<A: Scalar, F: Explicit<Scalar = A>> TimeStep for Heun<F> {
+impl<A: Scalar, F: NonAutonomousExplicit<Scalar = A>> TimeStep for Heun<F> {
     type Time = A::Real;
 
     fn get_dt(&self) -> Self::Time {
@@ -79,12 +172,32 @@ impl<A: Scalar, F: Explicit<Scalar = A>> TimeStep for Heun<F> {
     }
 }
 
-impl<F: Explicit> Scheme for Heun<F> {
+impl<A: Scalar, F: NonAutonomousExplicit<Scalar = A>> CurrentTime for Heun<F> {
+    fn get_time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+}
+
+impl<F: NonAutonomousExplicit> Scheme for Heun<F> {
     type Core = F;
     fn new(f: F, dt: Self::Time) -> Self {
         let x = Array::zeros(f.model_size());
         let k1 = Array::zeros(f.model_size());
-        Self { f, dt, x, k1 }
+        let t = F::Scalar::real(0.0);
+        Self {
+            f,
+            t,
+            dt,
+            last_dt: dt,
+            x,
+            k1,
+            dense: None,
+            tangent: None,
+        }
     }
     fn core(&self) -> &Self::Core {
         &self.f
@@ -94,7 +207,7 @@ impl<F: Explicit> Scheme for Heun<F> {
     }
 }
 
-impl<F: Explicit> ModelSpec for Heun<F> {
+impl<F: NonAutonomousExplicit> ModelSpec for Heun<F> {
     type Scalar = F::Scalar;
     type Dim = F::Dim;
     fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
@@ -102,7 +215,7 @@ impl<F: Explicit> ModelSpec for Heun<F> {
     }
 }
 
-impl<F: Explicit> TimeEvolution for Heun<F> {
+impl<F: NonAutonomousExplicit> TimeEvolution for Heun<F> {
     fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, F::Dim>) -> &'a mut ArrayBase<S, Self::Dim>
     where
         S: DataMut<Elem = Self::Scalar>,
@@ -111,33 +224,108 @@ impl<F: Explicit> TimeEvolution for Heun<F> {
         let dt_2 = self.dt * F::Scalar::real(0.5);
         // calc
         self.x.zip_mut_with(x, |buf, x| *buf = *x);
-        let k1 = self.f.rhs(x);
+        let k1 = self.f.rhs_t(self.t, x);
         self.k1.zip_mut_with(k1, |buf, k1| *buf = *k1);
         Zip::from(&mut *k1).and(&self.x).for_each(|k1, &x_| {
             *k1 = k1.mul_real(dt) + x_;
         });
-        let k2 = self.f.rhs(k1);
+        let k2 = self.f.rhs_t(self.t + dt, k1);
         Zip::from(&mut *k2)
             .and(&self.x)
             .and(&self.k1)
             .for_each(|k2, &x_, &k1_| {
                 *k2 = x_ + (k1_ + *k2).mul_real(dt_2);
             });
+        self.t += dt;
+        self.last_dt = dt;
+        if let Some(dense) = &mut self.dense {
+            dense.update(&mut self.f, self.t, dt, &self.x, Some(&self.k1), k2);
+        }
         k2
     }
 }
 
+impl<F: NonAutonomousExplicit> DenseOutput for Heun<F> {
+    fn set_dense_output(&mut self, dense: bool) {
+        self.dense = if dense {
+            Some(Hermite::new(self.f.model_size()))
+        } else {
+            None
+        };
+    }
+
+    fn last_dt(&self) -> Self::Time {
+        self.last_dt
+    }
+
+    fn dense_output<'a, S>(
+        &self,
+        theta: Self::Time,
+        x: &'a mut ArrayBase<S, F::Dim>,
+    ) -> &'a mut ArrayBase<S, F::Dim>
+    where
+        S: DataMut<Elem = F::Scalar>,
+    {
+        self.dense
+            .as_ref()
+            .expect("Dense output of Heun is disabled")
+            .eval(theta, x)
+    }
+}
+
+impl<F: AnalyticJacobian> TangentEvolution for Heun<F> {
+    fn iterate_tangent<'a, S, St>(
+        &mut self,
+        x: &'a mut ArrayBase<S, F::Dim>,
+        dx: &mut ArrayBase<St, <F::Dim as Dimension>::Larger>,
+    ) -> &'a mut ArrayBase<S, F::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+        St: DataMut<Elem = Self::Scalar>,
+        <F::Dim as Dimension>::Larger: RemoveAxis + Dimension<Smaller = F::Dim>,
+    {
+        let size = self.f.model_size();
+        let tangent = self.tangent.get_or_insert_with(|| TangentRK::new(size, 2));
+        tangent.step(
+            &mut self.f,
+            self.t,
+            x,
+            dx,
+            &[&[], &[1.0]],
+            &[0.5, 0.5],
+            self.dt,
+        );
+        self.t += self.dt;
+        self.last_dt = self.dt;
+        if let Some(dense) = &mut self.dense {
+            dense.update(
+                &mut self.f,
+                self.t,
+                self.dt,
+                &tangent.y[0],
+                Some(&tangent.k[0]),
+                x,
+            );
+        }
+        x
+    }
+}
+
 #[derive(Debug, Clone)]
-pub struct RK4<F: Explicit> {
+pub struct RK4<F: NonAutonomousExplicit> {
     f: F,
+    t: <F::Scalar as Scalar>::Real,
     dt: <F::Scalar as Scalar>::Real,
+    last_dt: <F::Scalar as Scalar>::Real,
     x: Array<F::Scalar, F::Dim>,
     k1: Array<F::Scalar, F::Dim>,
     k2: Array<F::Scalar, F::Dim>,
     k3: Array<F::Scalar, F::Dim>,
+    dense: Option<Hermite<F::Scalar, F::Dim>>,
+    tangent: Option<TangentRK<F::Scalar, F::Dim>>,
 }
 
-impl<A: Scalar, F: Explicit<Scalar = A>> TimeStep for RK4<F> {
+impl<A: Scalar, F: NonAutonomousExplicit<Scalar = A>> TimeStep for RK4<F> {
     type Time = A::Real;
 
     fn get_dt(&self) -> Self::Time {
@@ -149,7 +337,17 @@ impl<A: Scalar, F: Explicit<Scalar = A>> TimeStep for RK4<F> {
     }
 }
 
-impl<F: Explicit> Scheme for RK4<F> {
+impl<A: Scalar, F: NonAutonomousExplicit<Scalar = A>> CurrentTime for RK4<F> {
+    fn get_time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+}
+
+impl<F: NonAutonomousExplicit> Scheme for RK4<F> {
     type Core = F;
     fn new(f: F, dt: Self::Time) -> Self {
         let x = Array::zeros(f.model_size());
@@ -158,11 +356,15 @@ impl<F: Explicit> Scheme for RK4<F> {
         let k3 = Array::zeros(f.model_size());
         Self {
             f,
+            t: F::Scalar::real(0.0),
             dt,
+            last_dt: dt,
             x,
             k1,
             k2,
             k3,
+            dense: None,
+            tangent: None,
         }
     }
     fn core(&self) -> &Self::Core {
@@ -173,7 +375,7 @@ impl<F: Explicit> Scheme for RK4<F> {
     }
 }
 
-impl<F: Explicit> ModelSpec for RK4<F> {
+impl<F: NonAutonomousExplicit> ModelSpec for RK4<F> {
     type Scalar = F::Scalar;
     type Dim = F::Dim;
     fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
@@ -181,7 +383,7 @@ impl<F: Explicit> ModelSpec for RK4<F> {
     }
 }
 
-impl<F: Explicit> TimeEvolution for RK4<F> {
+impl<F: NonAutonomousExplicit> TimeEvolution for RK4<F> {
     fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, F::Dim>) -> &'a mut ArrayBase<S, Self::Dim>
     where
         S: DataMut<Elem = Self::Scalar>,
@@ -192,24 +394,24 @@ impl<F: Explicit> TimeEvolution for RK4<F> {
         let dt_6 = self.dt / F::Scalar::real(6.0);
         self.x.zip_mut_with(x, |buf, x| *buf = *x);
         // k1
-        let k1 = self.f.rhs(x);
+        let k1 = self.f.rhs_t(self.t, x);
         self.k1.zip_mut_with(k1, |buf, k1| *buf = *k1);
         Zip::from(&mut *k1).and(&self.x).for_each(|k1, &x| {
             *k1 = k1.mul_real(dt_2) + x;
         });
         // k2
-        let k2 = self.f.rhs(k1);
+        let k2 = self.f.rhs_t(self.t + dt_2, k1);
         self.k2.zip_mut_with(k2, |buf, k| *buf = *k);
         Zip::from(&mut *k2).and(&self.x).for_each(|k2, &x| {
             *k2 = x + k2.mul_real(dt_2);
         });
         // k3
-        let k3 = self.f.rhs(k2);
+        let k3 = self.f.rhs_t(self.t + dt_2, k2);
         self.k3.zip_mut_with(k3, |buf, k| *buf = *k);
         Zip::from(&mut *k3).and(&self.x).for_each(|k3, &x| {
             *k3 = x + k3.mul_real(dt);
         });
-        let k4 = self.f.rhs(k3);
+        let k4 = self.f.rhs_t(self.t + dt, k3);
         Zip::from(&mut *k4)
             .and(&self.x)
             .and(&self.k1)
@@ -218,6 +420,1133 @@ impl<F: Explicit> TimeEvolution for RK4<F> {
             .for_each(|k4, &x, &k1, &k2, &k3| {
                 *k4 = x + (k1 + (k2 + k3).mul_real(two) + *k4).mul_real(dt_6);
             });
+        self.t += dt;
+        self.last_dt = dt;
+        if let Some(dense) = &mut self.dense {
+            dense.update(&mut self.f, self.t, dt, &self.x, Some(&self.k1), k4);
+        }
         k4
     }
 }
+
+impl<F: NonAutonomousExplicit> DenseOutput for RK4<F> {
+    fn set_dense_output(&mut self, dense: bool) {
+        self.dense = if dense {
+            Some(Hermite::new(self.f.model_size()))
+        } else {
+            None
+        };
+    }
+
+    fn last_dt(&self) -> Self::Time {
+        self.last_dt
+    }
+
+    fn dense_output<'a, S>(
+        &self,
+        theta: Self::Time,
+        x: &'a mut ArrayBase<S, F::Dim>,
+    ) -> &'a mut ArrayBase<S, F::Dim>
+    where
+        S: DataMut<Elem = F::Scalar>,
+    {
+        self.dense
+            .as_ref()
+            .expect("Dense output of RK4 is disabled")
+            .eval(theta, x)
+    }
+}
+
+impl<F: AnalyticJacobian> TangentEvolution for RK4<F> {
+    fn iterate_tangent<'a, S, St>(
+        &mut self,
+        x: &'a mut ArrayBase<S, F::Dim>,
+        dx: &mut ArrayBase<St, <F::Dim as Dimension>::Larger>,
+    ) -> &'a mut ArrayBase<S, F::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+        St: DataMut<Elem = Self::Scalar>,
+        <F::Dim as Dimension>::Larger: RemoveAxis + Dimension<Smaller = F::Dim>,
+    {
+        let size = self.f.model_size();
+        let tangent = self.tangent.get_or_insert_with(|| TangentRK::new(size, 4));
+        tangent.step(
+            &mut self.f,
+            self.t,
+            x,
+            dx,
+            &[&[], &[0.5], &[0.0, 0.5], &[0.0, 0.0, 1.0]],
+            &[1.0 / 6.0, 1.0 / 3.0, 1.0 / 3.0, 1.0 / 6.0],
+            self.dt,
+        );
+        self.t += self.dt;
+        self.last_dt = self.dt;
+        if let Some(dense) = &mut self.dense {
+            dense.update(
+                &mut self.f,
+                self.t,
+                self.dt,
+                &tangent.y[0],
+                Some(&tangent.k[0]),
+                x,
+            );
+        }
+        x
+    }
+}
+
+/// Workspace of an explicit Runge-Kutta step with its tangent-linear operator,
+/// allocated at the first [TangentEvolution::iterate_tangent]
+#[derive(Debug, Clone)]
+struct TangentRK<A: Scalar, D: Dimension> {
+    /// States of the stages
+    y: Vec<Array<A, D>>,
+    /// Right hand sides at the stages
+    k: Vec<Array<A, D>>,
+    /// Tangent vectors at the stages
+    dk: Vec<Array<A, D>>,
+}
+
+impl<A: Scalar, D: Dimension> TangentRK<A, D> {
+    fn new<Sh: ShapeBuilder<Dim = D> + Clone>(size: Sh, stages: usize) -> Self {
+        let zeros =
+            || -> Vec<Array<A, D>> { (0..stages).map(|_| Array::zeros(size.clone())).collect() };
+        TangentRK {
+            y: zeros(),
+            k: zeros(),
+            dk: zeros(),
+        }
+    }
+
+    /// One step from `t` given by Butcher tableau `(a, b)`
+    /// with its tangent-linear operator applied to the columns of `dx`.
+    /// The initial state and the right hand side there are kept in `y[0]` and `k[0]`.
+    #[allow(clippy::too_many_arguments)]
+    fn step<F, S, St>(
+        &mut self,
+        f: &mut F,
+        t: A::Real,
+        x: &mut ArrayBase<S, D>,
+        dx: &mut ArrayBase<St, D::Larger>,
+        a: &[&[f64]],
+        b: &[f64],
+        dt: A::Real,
+    ) where
+        F: AnalyticJacobian<Scalar = A, Dim = D>,
+        S: DataMut<Elem = A>,
+        St: DataMut<Elem = A>,
+        D::Larger: RemoveAxis + Dimension<Smaller = D>,
+    {
+        for (i, a) in a.iter().enumerate() {
+            let (done, rest) = self.k.split_at_mut(i);
+            stage(&mut self.y[i], x, done, a, dt);
+            rest[0].assign(&self.y[i]);
+            let c = a.iter().sum::<f64>();
+            f.rhs_t(t + dt * A::real(c), &mut rest[0]);
+        }
+        let n = dx.ndim();
+        for mut dx in dx.axis_iter_mut(Axis(n - 1)) {
+            for (i, a) in a.iter().enumerate() {
+                let (done, rest) = self.dk.split_at_mut(i);
+                stage(&mut rest[0], &dx, done, a, dt);
+                f.jacobian(&self.y[i], &mut rest[0]);
+            }
+            add_combination(&mut dx, &self.dk, b, dt);
+        }
+        add_combination(x, &self.k, b, dt);
+    }
+}
+
+/// Set $y = x + \Delta t \sum_j a_j k_j$ for a row of Butcher tableau
+fn stage<A, D, S>(
+    y: &mut Array<A, D>,
+    x: &ArrayBase<S, D>,
+    k: &[Array<A, D>],
+    a: &[f64],
+    dt: A::Real,
+) where
+    A: Scalar,
+    D: Dimension,
+    S: Data<Elem = A>,
+{
+    y.zip_mut_with(x, |y, x| *y = *x);
+    add_combination(y, k, a, dt);
+}
+
+/// Add $\Delta t \sum_j a_j k_j$ to $y$
+fn add_combination<A, D, S>(y: &mut ArrayBase<S, D>, k: &[Array<A, D>], a: &[f64], dt: A::Real)
+where
+    A: Scalar,
+    D: Dimension,
+    S: DataMut<Elem = A>,
+{
+    for (k, &a) in k.iter().zip(a.iter()) {
+        if a == 0.0 {
+            continue;
+        }
+        let c = dt * A::real(a);
+        y.zip_mut_with(k, |y, k| *y += k.mul_real(c));
+    }
+}
+
+/// Root-mean-square norm of the local error `err` scaled by the tolerance
+/// `atol + rtol * max(|x|, |y|)`
+fn error_norm<A, D, S>(
+    err: &Array<A, D>,
+    x: &ArrayBase<S, D>,
+    y: &Array<A, D>,
+    atol: A::Real,
+    rtol: A::Real,
+) -> A::Real
+where
+    A: Scalar,
+    D: Dimension,
+    S: Data<Elem = A>,
+{
+    let mut sum = A::Real::zero();
+    Zip::from(err).and(x).and(y).for_each(|e, x, y| {
+        let sc = atol + rtol * Float::max(x.abs(), y.abs());
+        sum += (e.abs() / sc).square();
+    });
+    Float::sqrt(sum / A::real(err.len().max(1) as f64))
+}
+
+/// Dormand-Prince 5(4) scheme with adaptive step size
+///
+/// The local error is estimated by the embedded 4th order formula,
+/// and the step size is controlled by a PI controller.
+/// [TimeEvolution::iterate] proceeds one *accepted* step,
+/// whose length is available by [DormandPrince45::last_dt],
+/// and [TimeStep::get_dt] returns the step size which will be tried next.
+///
+/// The last stage of an accepted step is the first stage of the next step (FSAL),
+/// and is reused if the next step starts from the returned state.
+/// Thus each accepted step costs six evaluations of the right hand side.
+///
+/// [TimeEvolution::iterate] panics if the step size underflows to zero without an accepted step,
+/// which may happen for a stiff problem or a non-finite right hand side.
+/// [DormandPrince45::try_iterate] returns [Error::StepUnderflow] instead.
+///
+/// Links
+/// -----
+/// - E. Hairer, S. P. Nørsett, G. Wanner, "Solving Ordinary Differential Equations I", Sec. II.4-5
+#[derive(Debug, Clone)]
+pub struct DormandPrince45<F: NonAutonomousExplicit> {
+    f: F,
+    t: <F::Scalar as Scalar>::Real,
+    dt: <F::Scalar as Scalar>::Real,
+    last_dt: <F::Scalar as Scalar>::Real,
+    atol: <F::Scalar as Scalar>::Real,
+    rtol: <F::Scalar as Scalar>::Real,
+    err_old: <F::Scalar as Scalar>::Real,
+    rejected: usize,
+    dense: bool,
+    /// Whether `k[0]` is the right hand side at `(t, y)`
+    fsal: bool,
+    y: Array<F::Scalar, F::Dim>,
+    err: Array<F::Scalar, F::Dim>,
+    k: Vec<Array<F::Scalar, F::Dim>>,
+    rcont: Vec<Array<F::Scalar, F::Dim>>,
+}
+
+const DP45_C: [f64; 7] = [0.0, 1.0 / 5.0, 3.0 / 10.0, 4.0 / 5.0, 8.0 / 9.0, 1.0, 1.0];
+const DP45_A: [&[f64]; 7] = [
+    &[],
+    &[1.0 / 5.0],
+    &[3.0 / 40.0, 9.0 / 40.0],
+    &[44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0],
+    &[
+        19372.0 / 6561.0,
+        -25360.0 / 2187.0,
+        64448.0 / 6561.0,
+        -212.0 / 729.0,
+    ],
+    &[
+        9017.0 / 3168.0,
+        -355.0 / 33.0,
+        46732.0 / 5247.0,
+        49.0 / 176.0,
+        -5103.0 / 18656.0,
+    ],
+    &[
+        35.0 / 384.0,
+        0.0,
+        500.0 / 1113.0,
+        125.0 / 192.0,
+        -2187.0 / 6784.0,
+        11.0 / 84.0,
+    ],
+];
+/// Difference between 5th and embedded 4th order weights
+const DP45_E: [f64; 7] = [
+    71.0 / 57600.0,
+    0.0,
+    -71.0 / 16695.0,
+    71.0 / 1920.0,
+    -17253.0 / 339200.0,
+    22.0 / 525.0,
+    -1.0 / 40.0,
+];
+/// Coefficients of 4th order dense output by Shampine, as in `DOPRI5` code of Hairer et al.
+const DP45_D: [f64; 7] = [
+    -12715105075.0 / 11282082432.0,
+    0.0,
+    87487479700.0 / 32700410799.0,
+    -10690763975.0 / 1880347072.0,
+    701980252875.0 / 199316789632.0,
+    -1453857185.0 / 822651844.0,
+    69997945.0 / 29380423.0,
+];
+
+impl<F: NonAutonomousExplicit> DormandPrince45<F> {
+    /// Set absolute and relative tolerances of the local error (default: `1e-6` for both)
+    pub fn set_tolerance(
+        &mut self,
+        atol: <F::Scalar as Scalar>::Real,
+        rtol: <F::Scalar as Scalar>::Real,
+    ) {
+        self.atol = atol;
+        self.rtol = rtol;
+    }
+
+    /// Absolute and relative tolerances
+    pub fn tolerance(&self) -> (<F::Scalar as Scalar>::Real, <F::Scalar as Scalar>::Real) {
+        (self.atol, self.rtol)
+    }
+
+    /// Step size of the last accepted step
+    pub fn last_dt(&self) -> <F::Scalar as Scalar>::Real {
+        self.last_dt
+    }
+
+    /// Number of rejected steps since the scheme is created
+    pub fn rejected(&self) -> usize {
+        self.rejected
+    }
+
+    /// Evaluate the first stage at `(t, x)` unless it is reused from the last accepted step
+    fn first_stage<S>(&mut self, x: &ArrayBase<S, F::Dim>)
+    where
+        S: Data<Elem = F::Scalar>,
+    {
+        if self.fsal && *x == self.y {
+            return;
+        }
+        self.k[0].zip_mut_with(x, |k, x| *k = *x);
+        self.f.rhs_t(self.t, &mut self.k[0]);
+        self.fsal = false;
+    }
+
+    /// Try a step with the current `dt` from the first stage in `k[0]`,
+    /// and returns the scaled error norm. The result is stored in `self.y`.
+    fn try_step<S>(&mut self, x: &ArrayBase<S, F::Dim>) -> <F::Scalar as Scalar>::Real
+    where
+        S: Data<Elem = F::Scalar>,
+    {
+        let dt = self.dt;
+        for (i, (a, &c)) in DP45_A.iter().zip(DP45_C.iter()).enumerate().skip(1) {
+            let (done, rest) = self.k.split_at_mut(i);
+            let ki = &mut rest[0];
+            stage(ki, x, done, a, dt);
+            self.f.rhs_t(self.t + dt * F::Scalar::real(c), ki);
+        }
+        // 5th order solution is the input of the 7th stage (FSAL)
+        stage(&mut self.y, x, &self.k, DP45_A[6], dt);
+        self.err.fill(F::Scalar::zero());
+        add_combination(&mut self.err, &self.k, &DP45_E, dt);
+        error_norm(&self.err, x, &self.y, self.atol, self.rtol)
+    }
+
+    /// Prepare coefficients of the dense output for the accepted step from `x` to `self.y`
+    fn prepare_dense<S>(&mut self, x: &ArrayBase<S, F::Dim>)
+    where
+        S: Data<Elem = F::Scalar>,
+    {
+        let dt = self.last_dt;
+        let (r, rest) = self.rcont.split_at_mut(4);
+        r[0].zip_mut_with(x, |r, x| *r = *x);
+        Zip::from(&mut r[1])
+            .and(&self.y)
+            .and(x)
+            .for_each(|r, y, x| *r = *y - *x);
+        Zip::from(&mut r[2])
+            .and(&self.y)
+            .and(x)
+            .and(&self.k[0])
+            .for_each(|r, y, x, k1| *r = k1.mul_real(dt) - (*y - *x));
+        Zip::from(&mut r[3])
+            .and(&self.y)
+            .and(x)
+            .and(&self.k[0])
+            .and(&self.k[6])
+            .for_each(|r, y, x, k1, k7| {
+                *r = (*y - *x).mul_real(F::Scalar::real(2.0)) - (*k1 + *k7).mul_real(dt)
+            });
+        rest[0].fill(F::Scalar::zero());
+        add_combination(&mut rest[0], &self.k, &DP45_D, dt);
+    }
+}
+
+impl<F: NonAutonomousExplicit> DenseOutput for DormandPrince45<F> {
+    fn set_dense_output(&mut self, dense: bool) {
+        self.dense = dense;
+    }
+
+    fn last_dt(&self) -> Self::Time {
+        self.last_dt
+    }
+
+    fn dense_output<'a, S>(
+        &self,
+        theta: Self::Time,
+        x: &'a mut ArrayBase<S, F::Dim>,
+    ) -> &'a mut ArrayBase<S, F::Dim>
+    where
+        S: DataMut<Elem = F::Scalar>,
+    {
+        assert!(self.dense, "Dense output of DormandPrince45 is disabled");
+        let theta1 = F::Scalar::real(1.0) - theta;
+        x.zip_mut_with(&self.rcont[4], |x, r| *x = *r);
+        for (i, r) in self.rcont[..4].iter().enumerate().rev() {
+            let c = if i % 2 == 0 { theta } else { theta1 };
+            x.zip_mut_with(r, |x, r| *x = *r + x.mul_real(c));
+        }
+        x
+    }
+}
+
+impl<A: Scalar, F: NonAutonomousExplicit<Scalar = A>> TimeStep for DormandPrince45<F> {
+    type Time = A::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
+        self.fsal = false;
+    }
+}
+
+impl<A: Scalar, F: NonAutonomousExplicit<Scalar = A>> CurrentTime for DormandPrince45<F> {
+    fn get_time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+        self.fsal = false;
+    }
+}
+
+impl<F: NonAutonomousExplicit> Scheme for DormandPrince45<F> {
+    type Core = F;
+    fn new(f: F, dt: Self::Time) -> Self {
+        let y = Array::zeros(f.model_size());
+        let err = Array::zeros(f.model_size());
+        let k = (0..7).map(|_| Array::zeros(f.model_size())).collect();
+        let rcont = (0..5).map(|_| Array::zeros(f.model_size())).collect();
+        let tol = F::Scalar::real(1e-6);
+        Self {
+            f,
+            t: F::Scalar::real(0.0),
+            dt,
+            last_dt: dt,
+            atol: tol,
+            rtol: tol,
+            err_old: F::Scalar::real(1e-4),
+            rejected: 0,
+            dense: false,
+            fsal: false,
+            y,
+            err,
+            k,
+            rcont,
+        }
+    }
+    fn core(&self) -> &Self::Core {
+        &self.f
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        // the model may be modified
+        self.fsal = false;
+        &mut self.f
+    }
+}
+
+impl<F: NonAutonomousExplicit> ModelSpec for DormandPrince45<F> {
+    type Scalar = F::Scalar;
+    type Dim = F::Dim;
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.f.model_size()
+    }
+}
+
+impl<F: NonAutonomousExplicit> DormandPrince45<F> {
+    /// Proceed one accepted step as [TimeEvolution::iterate]
+    ///
+    /// Returns [Error::StepUnderflow] if the step size underflows to zero,
+    /// where `x`, the time and the step size are left unchanged.
+    pub fn try_iterate<'a, S>(
+        &mut self,
+        x: &'a mut ArrayBase<S, F::Dim>,
+    ) -> Result<&'a mut ArrayBase<S, F::Dim>>
+    where
+        S: DataMut<Elem = F::Scalar>,
+    {
+        let real = F::Scalar::real;
+        let one = real(1.0);
+        // PI controller parameters, see Hairer-Nørsett-Wanner Sec. IV.2
+        let beta = real(0.04);
+        let expo = real(0.2) - beta * real(0.75);
+        let safety = real(0.9);
+        let (fac_min, fac_max) = (real(0.2), real(10.0));
+        let dt0 = self.dt;
+        self.first_stage(x);
+        loop {
+            let err = self.try_step(x);
+            if err <= one {
+                let err = Float::max(err, real(1e-10));
+                let fac = safety * Float::powf(self.err_old, beta) / Float::powf(err, expo);
+                self.last_dt = self.dt;
+                self.dt *= Float::min(fac_max, Float::max(fac_min, fac));
+                self.err_old = Float::max(err, real(1e-4));
+                self.t += self.last_dt;
+                if self.dense {
+                    self.prepare_dense(x);
+                }
+                self.k.swap(0, 6);
+                self.fsal = true;
+                break;
+            }
+            self.rejected += 1;
+            let fac = if err.is_finite() {
+                safety / Float::powf(err, expo)
+            } else {
+                fac_min
+            };
+            self.dt *= Float::max(fac_min, Float::min(one, fac));
+            if self.dt <= <F::Scalar as Scalar>::Real::zero() {
+                self.dt = dt0;
+                return Err(Error::StepUnderflow);
+            }
+        }
+        x.zip_mut_with(&self.y, |x, y| *x = *y);
+        Ok(x)
+    }
+}
+
+impl<F: NonAutonomousExplicit> TimeEvolution for DormandPrince45<F> {
+    fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, F::Dim>) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        self.try_iterate(x)
+            .unwrap_or_else(|e| panic!("{} in DormandPrince45", e))
+    }
+}
+
+/// Dormand-Prince 8(5,3) scheme with adaptive step size
+///
+/// The local error is estimated by the combination of embedded 5th and 3rd order formulas
+/// as in `DOP853` code of Hairer et al.
+/// Similar to [DormandPrince45], [TimeEvolution::iterate] proceeds one accepted step,
+/// and [TimeStep::get_dt] returns the step size which will be tried next.
+/// The step size is kept within the range set by [DOP853::set_dt_limits].
+/// A step of the lower limit is accepted even if the error exceeds the tolerance,
+/// and counted by [DOP853::forced], unless the error is not finite.
+/// [TimeEvolution::iterate] panics if no step is accepted at the lower limit,
+/// or the step size underflows to zero, and [DOP853::try_iterate] returns [Error::StepUnderflow] instead.
+///
+/// The step size control can be disabled by [DOP853::set_adaptive],
+/// which makes this a fixed step 8th order scheme, e.g. for [adaptor::accuracy].
+/// The 7th order dense output is enabled by [DenseOutput::set_dense_output],
+/// which costs three additional evaluations of $f$ for each step.
+///
+/// Links
+/// -----
+/// - E. Hairer, S. P. Nørsett, G. Wanner, "Solving Ordinary Differential Equations I", Sec. II.10
+///
+/// [adaptor::accuracy]: crate::adaptor::accuracy
+#[derive(Debug, Clone)]
+pub struct DOP853<F: NonAutonomousExplicit> {
+    f: F,
+    t: <F::Scalar as Scalar>::Real,
+    dt: <F::Scalar as Scalar>::Real,
+    last_dt: <F::Scalar as Scalar>::Real,
+    dt_min: <F::Scalar as Scalar>::Real,
+    dt_max: <F::Scalar as Scalar>::Real,
+    atol: <F::Scalar as Scalar>::Real,
+    rtol: <F::Scalar as Scalar>::Real,
+    rejected: usize,
+    forced: usize,
+    adaptive: bool,
+    dense: bool,
+    x: Array<F::Scalar, F::Dim>,
+    y: Array<F::Scalar, F::Dim>,
+    err5: Array<F::Scalar, F::Dim>,
+    err3: Array<F::Scalar, F::Dim>,
+    k: Vec<Array<F::Scalar, F::Dim>>,
+    rcont: Vec<Array<F::Scalar, F::Dim>>,
+}
+
+/// Rows of Butcher tableau for 2-16th stages.
+/// The 13th stage is the evaluation at the new state, and its row is kept as a placeholder.
+const DOP853_A: [&[f64]; 15] = [
+    &[0.05260015195876773],
+    &[0.0197250569845379, 0.0591751709536137],
+    &[0.02958758547680685, 0.0, 0.08876275643042054],
+    &[
+        0.2413651341592667,
+        0.0,
+        -0.8845494793282861,
+        0.924834003261792,
+    ],
+    &[
+        0.037037037037037035,
+        0.0,
+        0.0,
+        0.17082860872947386,
+        0.12546768756682242,
+    ],
+    &[
+        0.037109375,
+        0.0,
+        0.0,
+        0.17025221101954405,
+        0.06021653898045596,
+        -0.017578125,
+    ],
+    &[
+        0.03709200011850479,
+        0.0,
+        0.0,
+        0.17038392571223998,
+        0.10726203044637328,
+        -0.015319437748624402,
+        0.008273789163814023,
+    ],
+    &[
+        0.6241109587160757,
+        0.0,
+        0.0,
+        -3.3608926294469414,
+        -0.868219346841726,
+        27.59209969944671,
+        20.154067550477894,
+        -43.48988418106996,
+    ],
+    &[
+        0.47766253643826434,
+        0.0,
+        0.0,
+        -2.4881146199716677,
+        -0.590290826836843,
+        21.230051448181193,
+        15.279233632882423,
+        -33.28821096898486,
+        -0.020331201708508627,
+    ],
+    &[
+        -0.9371424300859873,
+        0.0,
+        0.0,
+        5.186372428844064,
+        1.0914373489967295,
+        -8.149787010746927,
+        -18.52006565999696,
+        22.739487099350505,
+        2.4936055526796523,
+        -3.0467644718982196,
+    ],
+    &[
+        2.273310147516538,
+        0.0,
+        0.0,
+        -10.53449546673725,
+        -2.0008720582248625,
+        -17.9589318631188,
+        27.94888452941996,
+        -2.8589982771350235,
+        -8.87285693353063,
+        12.360567175794303,
+        0.6433927460157636,
+    ],
+    &[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    &[
+        0.056167502283047954,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.25350021021662483,
+        -0.2462390374708025,
+        -0.12419142326381637,
+        0.15329179827876568,
+        0.00820105229563469,
+        0.007567897660545699,
+        -0.008298,
+    ],
+    &[
+        0.03183464816350214,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.028300909672366776,
+        0.053541988307438566,
+        -0.05492374857139099,
+        0.0,
+        0.0,
+        -0.00010834732869724932,
+        0.0003825710908356584,
+        -0.00034046500868740456,
+        0.1413124436746325,
+    ],
+    &[
+        -0.42889630158379194,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        -4.697621415361164,
+        7.683421196062599,
+        4.06898981839711,
+        0.3567271874552811,
+        0.0,
+        0.0,
+        0.0,
+        -0.0013990241651590145,
+        2.9475147891527724,
+        -9.15095847217987,
+    ],
+];
+/// 8th order weights
+const DOP853_B: [f64; 12] = [
+    0.054293734116568765,
+    0.0,
+    0.0,
+    0.0,
+    0.0,
+    4.450312892752409,
+    1.8915178993145003,
+    -5.801203960010585,
+    0.3111643669578199,
+    -0.1521609496625161,
+    0.20136540080403034,
+    0.04471061572777259,
+];
+/// Nodes of 1-16th stages
+const DOP853_C: [f64; 16] = [
+    0.0,
+    0.05260015195876773,
+    0.0789002279381516,
+    0.1183503419072274,
+    0.2816496580927726,
+    1.0 / 3.0,
+    0.25,
+    4.0 / 13.0,
+    127.0 / 195.0,
+    0.6,
+    6.0 / 7.0,
+    1.0,
+    1.0,
+    0.1,
+    0.2,
+    7.0 / 9.0,
+];
+/// Weights of embedded 3rd order formula
+const DOP853_BHH: [f64; 3] = [0.2440944881889764, 0.7338466882816118, 0.022058823529411766];
+/// Difference between 8th and embedded 5th order weights
+const DOP853_E: [f64; 16] = [
+    0.01312004499419488,
+    0.0,
+    0.0,
+    0.0,
+    0.0,
+    -1.2251564463762044,
+    -0.4957589496572502,
+    1.6643771824549864,
+    -0.35032884874997366,
+    0.3341791187130175,
+    0.08192320648511571,
+    -0.022355307863886294,
+    0.0,
+    0.0,
+    0.0,
+    0.0,
+];
+/// Coefficients of 7th order dense output
+const DOP853_D: [[f64; 16]; 4] = [
+    [
+        -8.428938276109013,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.5667149535193777,
+        -3.0689499459498917,
+        2.38466765651207,
+        2.117034582445028,
+        -0.871391583777973,
+        2.2404374302607883,
+        0.6315787787694688,
+        -0.08899033645133331,
+        18.148505520854727,
+        -9.194632392478356,
+        -4.436036387594894,
+    ],
+    [
+        10.427508642579134,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        242.28349177525817,
+        165.20045171727028,
+        -374.5467547226902,
+        -22.113666853125306,
+        7.733432668472264,
+        -30.674084731089398,
+        -9.332130526430229,
+        15.697238121770845,
+        -31.139403219565178,
+        -9.35292435884448,
+        35.81684148639408,
+    ],
+    [
+        19.985053242002433,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        -387.0373087493518,
+        -189.17813819516758,
+        527.8081592054236,
+        -11.57390253995963,
+        6.8812326946963,
+        -1.0006050966910838,
+        0.7777137798053443,
+        -2.778205752353508,
+        -60.19669523126412,
+        84.32040550667716,
+        11.99229113618279,
+    ],
+    [
+        -25.69393346270375,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        -154.18974869023643,
+        -231.5293791760455,
+        357.6391179106141,
+        93.40532418362432,
+        -37.45832313645163,
+        104.0996495089623,
+        29.8402934266605,
+        -43.53345659001114,
+        96.32455395918828,
+        -39.17726167561544,
+        -149.72683625798564,
+    ],
+];
+
+impl<F: NonAutonomousExplicit> DOP853<F> {
+    /// Set absolute and relative tolerances of the local error (default: `1e-9` for both)
+    pub fn set_tolerance(
+        &mut self,
+        atol: <F::Scalar as Scalar>::Real,
+        rtol: <F::Scalar as Scalar>::Real,
+    ) {
+        self.atol = atol;
+        self.rtol = rtol;
+    }
+
+    /// Absolute and relative tolerances
+    pub fn tolerance(&self) -> (<F::Scalar as Scalar>::Real, <F::Scalar as Scalar>::Real) {
+        (self.atol, self.rtol)
+    }
+
+    /// Set the lower and upper limits of the step size (default: `0` and `inf`)
+    pub fn set_dt_limits(
+        &mut self,
+        dt_min: <F::Scalar as Scalar>::Real,
+        dt_max: <F::Scalar as Scalar>::Real,
+    ) {
+        self.dt_min = dt_min;
+        self.dt_max = dt_max;
+        self.dt = Float::min(dt_max, Float::max(dt_min, self.dt));
+    }
+
+    /// Enable or disable the step size control (default: enabled)
+    pub fn set_adaptive(&mut self, adaptive: bool) {
+        self.adaptive = adaptive;
+    }
+
+    /// Step size of the last accepted step
+    pub fn last_dt(&self) -> <F::Scalar as Scalar>::Real {
+        self.last_dt
+    }
+
+    /// Number of rejected steps since the scheme is created
+    pub fn rejected(&self) -> usize {
+        self.rejected
+    }
+
+    /// Number of steps accepted at the lower limit of the step size
+    /// although the error exceeds the tolerance
+    pub fn forced(&self) -> usize {
+        self.forced
+    }
+
+    /// Try a step with the current `dt`, and returns the scaled error norm.
+    /// The result is stored in `self.y`.
+    fn try_step<S>(&mut self, x: &ArrayBase<S, F::Dim>) -> <F::Scalar as Scalar>::Real
+    where
+        S: Data<Elem = F::Scalar>,
+    {
+        let real = F::Scalar::real;
+        let dt = self.dt;
+        for i in 0..12 {
+            let (done, rest) = self.k.split_at_mut(i);
+            let ki = &mut rest[0];
+            let a = if i == 0 { &[][..] } else { DOP853_A[i - 1] };
+            stage(ki, x, done, a, dt);
+            self.f.rhs_t(self.t + dt * real(DOP853_C[i]), ki);
+        }
+        stage(&mut self.y, x, &self.k, &DOP853_B, dt);
+        if !self.adaptive {
+            return real(0.0);
+        }
+        let mut b3 = DOP853_B;
+        b3[0] -= DOP853_BHH[0];
+        b3[8] -= DOP853_BHH[1];
+        b3[11] -= DOP853_BHH[2];
+        self.err5.fill(F::Scalar::zero());
+        add_combination(&mut self.err5, &self.k, &DOP853_E, real(1.0));
+        self.err3.fill(F::Scalar::zero());
+        add_combination(&mut self.err3, &self.k, &b3, real(1.0));
+        let mut err5 = real(0.0);
+        let mut err3 = real(0.0);
+        Zip::from(&self.err5)
+            .and(&self.err3)
+            .and(x)
+            .and(&self.y)
+            .for_each(|e5, e3, x, y| {
+                let sc = self.atol + self.rtol * Float::max(x.abs(), y.abs());
+                err5 += (e5.abs() / sc).square();
+                err3 += (e3.abs() / sc).square();
+            });
+        let mut deno = err5 + real(0.01) * err3;
+        if deno <= real(0.0) {
+            deno = real(1.0);
+        }
+        dt * err5 / Float::sqrt(deno * real(self.y.len().max(1) as f64))
+    }
+
+    /// Prepare coefficients of the dense output for the accepted step from `self.x` to `self.y`
+    fn prepare_dense(&mut self) {
+        let dt = self.last_dt;
+        let t = self.t - dt;
+        let two = F::Scalar::real(2.0);
+        // 13th stage: f at the new state
+        self.k[12].zip_mut_with(&self.y, |k, y| *k = *y);
+        self.f.rhs_t(self.t, &mut self.k[12]);
+        // extra stages for the dense output
+        for i in 13..16 {
+            let (done, rest) = self.k.split_at_mut(i);
+            let ki = &mut rest[0];
+            stage(ki, &self.x, done, DOP853_A[i - 1], dt);
+            self.f.rhs_t(t + dt * F::Scalar::real(DOP853_C[i]), ki);
+        }
+        let (r, rest) = self.rcont.split_at_mut(4);
+        r[0].zip_mut_with(&self.x, |r, x| *r = *x);
+        Zip::from(&mut r[1])
+            .and(&self.y)
+            .and(&self.x)
+            .for_each(|r, y, x| *r = *y - *x);
+        Zip::from(&mut r[2])
+            .and(&self.y)
+            .and(&self.x)
+            .and(&self.k[0])
+            .for_each(|r, y, x, k1| *r = k1.mul_real(dt) - (*y - *x));
+        Zip::from(&mut r[3])
+            .and(&self.y)
+            .and(&self.x)
+            .and(&self.k[0])
+            .and(&self.k[12])
+            .for_each(|r, y, x, k1, k13| *r = (*y - *x).mul_real(two) - (*k1 + *k13).mul_real(dt));
+        for (r, d) in rest.iter_mut().zip(DOP853_D.iter()) {
+            r.fill(F::Scalar::zero());
+            add_combination(r, &self.k, d, dt);
+        }
+    }
+}
+
+impl<F: NonAutonomousExplicit> DenseOutput for DOP853<F> {
+    fn set_dense_output(&mut self, dense: bool) {
+        self.dense = dense;
+    }
+
+    fn last_dt(&self) -> Self::Time {
+        self.last_dt
+    }
+
+    fn dense_output<'a, S>(
+        &self,
+        theta: <F::Scalar as Scalar>::Real,
+        x: &'a mut ArrayBase<S, F::Dim>,
+    ) -> &'a mut ArrayBase<S, F::Dim>
+    where
+        S: DataMut<Elem = F::Scalar>,
+    {
+        assert!(self.dense, "Dense output of DOP853 is disabled");
+        let theta1 = F::Scalar::real(1.0) - theta;
+        x.zip_mut_with(&self.rcont[7], |x, r| *x = *r);
+        for (i, r) in self.rcont[..7].iter().enumerate().rev() {
+            let c = if i % 2 == 0 { theta } else { theta1 };
+            x.zip_mut_with(r, |x, r| *x = *r + x.mul_real(c));
+        }
+        x
+    }
+}
+
+impl<A: Scalar, F: NonAutonomousExplicit<Scalar = A>> TimeStep for DOP853<F> {
+    type Time = A::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    /// The step size is clamped into the limits set by [DOP853::set_dt_limits]
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = Float::min(self.dt_max, Float::max(self.dt_min, dt));
+    }
+}
+
+impl<A: Scalar, F: NonAutonomousExplicit<Scalar = A>> CurrentTime for DOP853<F> {
+    fn get_time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+}
+
+impl<F: NonAutonomousExplicit> Scheme for DOP853<F> {
+    type Core = F;
+    fn new(f: F, dt: Self::Time) -> Self {
+        let x = Array::zeros(f.model_size());
+        let y = Array::zeros(f.model_size());
+        let err5 = Array::zeros(f.model_size());
+        let err3 = Array::zeros(f.model_size());
+        let k = (0..16).map(|_| Array::zeros(f.model_size())).collect();
+        let rcont = (0..8).map(|_| Array::zeros(f.model_size())).collect();
+        let tol = F::Scalar::real(1e-9);
+        Self {
+            f,
+            t: F::Scalar::real(0.0),
+            dt,
+            last_dt: dt,
+            dt_min: F::Scalar::real(0.0),
+            dt_max: <F::Scalar as Scalar>::Real::infinity(),
+            atol: tol,
+            rtol: tol,
+            rejected: 0,
+            forced: 0,
+            adaptive: true,
+            dense: false,
+            x,
+            y,
+            err5,
+            err3,
+            k,
+            rcont,
+        }
+    }
+    fn core(&self) -> &Self::Core {
+        &self.f
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.f
+    }
+}
+
+impl<F: NonAutonomousExplicit> ModelSpec for DOP853<F> {
+    type Scalar = F::Scalar;
+    type Dim = F::Dim;
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.f.model_size()
+    }
+}
+
+impl<F: NonAutonomousExplicit> DOP853<F> {
+    /// Proceed one accepted step as [TimeEvolution::iterate]
+    ///
+    /// Returns [Error::StepUnderflow] if the error is not finite at the lower limit of the step size,
+    /// or the step size underflows to zero,
+    /// where `x`, the time and the step size are left unchanged.
+    pub fn try_iterate<'a, S>(
+        &mut self,
+        x: &'a mut ArrayBase<S, F::Dim>,
+    ) -> Result<&'a mut ArrayBase<S, F::Dim>>
+    where
+        S: DataMut<Elem = F::Scalar>,
+    {
+        let real = F::Scalar::real;
+        let one = real(1.0);
+        let safety = real(0.9);
+        let (fac_min, fac_max) = (real(0.333), real(6.0));
+        let expo = real(1.0 / 8.0);
+        let dt0 = self.dt;
+        loop {
+            let err = self.try_step(x);
+            let accepted = err <= one;
+            let at_limit = self.dt <= self.dt_min;
+            if accepted || (at_limit && err.is_finite()) {
+                if !accepted {
+                    self.forced += 1;
+                }
+                self.last_dt = self.dt;
+                if self.adaptive {
+                    let fac = safety / Float::powf(Float::max(err, real(1e-10)), expo);
+                    self.dt *= Float::min(fac_max, Float::max(fac_min, fac));
+                    self.dt = Float::min(self.dt_max, Float::max(self.dt_min, self.dt));
+                }
+                self.t += self.last_dt;
+                break;
+            }
+            self.rejected += 1;
+            let fac = if err.is_finite() {
+                safety / Float::powf(err, expo)
+            } else {
+                fac_min
+            };
+            self.dt *= Float::max(fac_min, Float::min(one, fac));
+            self.dt = Float::max(self.dt_min, self.dt);
+            if at_limit || self.dt <= <F::Scalar as Scalar>::Real::zero() {
+                self.dt = dt0;
+                return Err(Error::StepUnderflow);
+            }
+        }
+        if self.dense {
+            self.x.zip_mut_with(x, |buf, x| *buf = *x);
+            self.prepare_dense();
+        }
+        x.zip_mut_with(&self.y, |x, y| *x = *y);
+        Ok(x)
+    }
+}
+
+impl<F: NonAutonomousExplicit> TimeEvolution for DOP853<F> {
+    fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, F::Dim>) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        self.try_iterate(x)
+            .unwrap_or_else(|e| panic!("{} in DOP853", e))
+    }
+}
@@ -0,0 +1,87 @@
+//! Cubic Hermite interpolation shared by the dense output and the event detection
+
+use ndarray::*;
+use ndarray_linalg::*;
+use num_traits::Zero;
+
+use crate::traits::*;
+
+/// Cubic Hermite interpolation of a step
+///
+/// Used as the dense output of the fixed step schemes in [crate::explicit]
+/// and to locate the events in [crate::event::Detector]
+#[derive(Debug, Clone)]
+pub(crate) struct Hermite<A: Scalar, D: Dimension> {
+    dt: A::Real,
+    x0: Array<A, D>,
+    f0: Array<A, D>,
+    x1: Array<A, D>,
+    f1: Array<A, D>,
+}
+
+impl<A: Scalar, D: Dimension> Hermite<A, D> {
+    pub(crate) fn new<Sh: ShapeBuilder<Dim = D>>(size: Sh) -> Self {
+        let x0 = Array::zeros(size);
+        Hermite {
+            dt: A::Real::zero(),
+            f0: x0.clone(),
+            x1: x0.clone(),
+            f1: x0.clone(),
+            x0,
+        }
+    }
+
+    /// Store the step from `x0` to `x1` ending at `t1`.
+    /// `f0` is reconstructed as $(x_1 - x_0) / \Delta t$ if not given, which is exact for the Euler scheme.
+    pub(crate) fn update<F, S>(
+        &mut self,
+        f: &mut F,
+        t1: A::Real,
+        dt: A::Real,
+        x0: &Array<A, D>,
+        f0: Option<&Array<A, D>>,
+        x1: &ArrayBase<S, D>,
+    ) where
+        F: NonAutonomousExplicit<Scalar = A, Dim = D>,
+        S: Data<Elem = A>,
+    {
+        self.dt = dt;
+        self.x0.assign(x0);
+        self.x1.assign(x1);
+        match f0 {
+            Some(f0) => self.f0.assign(f0),
+            None => Zip::from(&mut self.f0)
+                .and(x0)
+                .and(x1)
+                .for_each(|f0, x0, x1| *f0 = (*x1 - *x0).div_real(dt)),
+        }
+        self.f1.assign(x1);
+        f.rhs_t(t1, &mut self.f1);
+    }
+
+    pub(crate) fn eval<'a, S>(
+        &self,
+        theta: A::Real,
+        x: &'a mut ArrayBase<S, D>,
+    ) -> &'a mut ArrayBase<S, D>
+    where
+        S: DataMut<Elem = A>,
+    {
+        let real = A::real;
+        let t2 = theta * theta;
+        let t3 = t2 * theta;
+        let h00 = real(2.0) * t3 - real(3.0) * t2 + real(1.0);
+        let h10 = (t3 - real(2.0) * t2 + theta) * self.dt;
+        let h01 = real(3.0) * t2 - real(2.0) * t3;
+        let h11 = (t3 - t2) * self.dt;
+        Zip::from(&mut *x)
+            .and(&self.x0)
+            .and(&self.f0)
+            .and(&self.x1)
+            .and(&self.f1)
+            .for_each(|x, &x0, &f0, &x1, &f1| {
+                *x = x0.mul_real(h00) + f0.mul_real(h10) + x1.mul_real(h01) + f1.mul_real(h11);
+            });
+        x
+    }
+}
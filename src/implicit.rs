@@ -0,0 +1,527 @@
+//! implicit schemes
+
+use ndarray::*;
+use ndarray_linalg::*;
+use num_traits::{Float, One, ToPrimitive, Zero};
+
+use super::error::{Error, Result};
+use super::traits::*;
+
+/// Maximum order of [BDF]
+const BDF_MAX_ORDER: usize = 5;
+/// Maximum number of Newton iterations in a step
+const BDF_NEWTON_MAXITER: usize = 4;
+const BDF_MIN_FACTOR: f64 = 0.2;
+const BDF_MAX_FACTOR: f64 = 10.0;
+
+/// User-supplied Jacobian matrix $\partial f / \partial x$ at $(x, t)$
+pub type JacobianFn<F> = fn(
+    &mut F,
+    <<F as ModelSpec>::Scalar as Scalar>::Real,
+    &Array1<<F as ModelSpec>::Scalar>,
+) -> Array2<<F as ModelSpec>::Scalar>;
+
+/// Dense Jacobian matrix of [AnalyticJacobian] models, which can be used in [BDF::set_jacobian]
+pub fn analytic_jacobian<F>(
+    f: &mut F,
+    _t: <F::Scalar as Scalar>::Real,
+    x: &Array1<F::Scalar>,
+) -> Array2<F::Scalar>
+where
+    F: AnalyticJacobian<Dim = Ix1>,
+{
+    let n = x.len();
+    let mut jac = Array2::eye(n);
+    for mut col in jac.axis_iter_mut(Axis(1)) {
+        f.jacobian(x, &mut col);
+    }
+    jac
+}
+
+#[cfg_attr(doc, katexit::katexit)]
+/// Variable-order, variable-step backward differentiation formula (BDF) for stiff equations
+///
+/// The order is chosen from 1 to 5 and the step size is controlled
+/// to keep the local error within the tolerances set by [BDF::set_tolerance].
+/// The implicit equation of each step is solved by the simplified Newton iteration,
+/// where the LU factorization of $I - c J$ is reused while the iteration converges.
+/// The Jacobian $J = \partial f / \partial x$ is evaluated by the finite difference,
+/// or given by [BDF::set_jacobian], e.g. [analytic_jacobian] for models implementing [AnalyticJacobian].
+///
+/// Similar to [DormandPrince45], [TimeEvolution::iterate] proceeds one accepted step,
+/// and [TimeStep::get_dt] returns the step size which will be tried next.
+/// The history of the previous steps is kept in the scheme,
+/// and it is discarded if the state given to [TimeEvolution::iterate]
+/// is not the result of the previous step.
+///
+/// [TimeEvolution::iterate] panics if the step size underflows to zero without an accepted step,
+/// and [BDF::try_iterate] returns [Error::StepUnderflow] instead.
+///
+/// Links
+/// -----
+/// - L. F. Shampine and M. W. Reichelt, "The MATLAB ODE Suite", SIAM J. Sci. Comput. 18, 1 (1997)
+/// - E. Hairer and G. Wanner, "Solving Ordinary Differential Equations II", Sec. III.5
+///
+/// [DormandPrince45]: crate::explicit::DormandPrince45
+#[derive(Clone)]
+pub struct BDF<F>
+where
+    F: NonAutonomousExplicit<Dim = Ix1>,
+    F::Scalar: Lapack,
+{
+    f: F,
+    t: <F::Scalar as Scalar>::Real,
+    dt: <F::Scalar as Scalar>::Real,
+    last_dt: <F::Scalar as Scalar>::Real,
+    atol: <F::Scalar as Scalar>::Real,
+    rtol: <F::Scalar as Scalar>::Real,
+    order: usize,
+    n_equal_steps: usize,
+    rejected: usize,
+    /// Modified divided differences of previous steps
+    d: Array2<F::Scalar>,
+    /// The state after the last step
+    y: Option<Array1<F::Scalar>>,
+    jac: Option<Array2<F::Scalar>>,
+    jac_current: bool,
+    lu: Option<LUFactorized<OwnedRepr<F::Scalar>>>,
+    jacobian: Option<JacobianFn<F>>,
+}
+
+/// Solution of Newton iteration, its difference from the prediction, and the number of iterations
+type NewtonResult<A> = (Array1<A>, Array1<A>, usize);
+
+/// Matrix to change the step size of divided differences by `factor`
+fn compute_r(order: usize, factor: f64) -> Array2<f64> {
+    let mut m = Array2::zeros((order + 1, order + 1));
+    m.row_mut(0).fill(1.0);
+    for i in 1..=order {
+        for j in 1..=order {
+            m[(i, j)] = (i as f64 - 1.0 - factor * j as f64) / i as f64;
+        }
+    }
+    // cumulative product along rows
+    for i in 1..=order {
+        for j in 0..=order {
+            m[(i, j)] *= m[(i - 1, j)];
+        }
+    }
+    m
+}
+
+/// Root-mean-square norm of `x / scale`
+fn rms_norm<A: Scalar>(x: &Array1<A>, scale: &Array1<A::Real>) -> A::Real {
+    let mut sum = A::Real::zero();
+    Zip::from(x)
+        .and(scale)
+        .for_each(|x, &sc| sum += (x.abs() / sc).square());
+    Float::sqrt(sum / A::real(x.len().max(1) as f64))
+}
+
+impl<F> BDF<F>
+where
+    F: NonAutonomousExplicit<Dim = Ix1>,
+    F::Scalar: Lapack,
+{
+    /// Set absolute and relative tolerances of the local error (default: `1e-6` for both)
+    pub fn set_tolerance(
+        &mut self,
+        atol: <F::Scalar as Scalar>::Real,
+        rtol: <F::Scalar as Scalar>::Real,
+    ) {
+        self.atol = atol;
+        self.rtol = rtol;
+    }
+
+    /// Absolute and relative tolerances
+    pub fn tolerance(&self) -> (<F::Scalar as Scalar>::Real, <F::Scalar as Scalar>::Real) {
+        (self.atol, self.rtol)
+    }
+
+    /// Use the user-supplied Jacobian instead of the finite difference
+    pub fn set_jacobian(&mut self, jacobian: JacobianFn<F>) {
+        self.jacobian = Some(jacobian);
+        self.jac = None;
+        self.lu = None;
+    }
+
+    /// Current order of the formula
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Step size of the last accepted step
+    pub fn last_dt(&self) -> <F::Scalar as Scalar>::Real {
+        self.last_dt
+    }
+
+    /// Number of rejected steps since the scheme is created
+    pub fn rejected(&self) -> usize {
+        self.rejected
+    }
+
+    /// Evaluate the Jacobian at $(x, t)$
+    fn eval_jacobian(
+        &mut self,
+        t: <F::Scalar as Scalar>::Real,
+        x: &Array1<F::Scalar>,
+    ) -> Array2<F::Scalar> {
+        if let Some(jacobian) = self.jacobian {
+            return jacobian(&mut self.f, t, x);
+        }
+        let n = x.len();
+        let sqrt_eps = Float::sqrt(<F::Scalar as Scalar>::Real::epsilon());
+        let mut fx = x.clone();
+        self.f.rhs_t(t, &mut fx);
+        let mut jac = Array2::zeros((n, n));
+        for j in 0..n {
+            let h = sqrt_eps * Float::max(F::Scalar::real(1.0), x[j].abs());
+            let mut xh = x.clone();
+            xh[j] = xh[j].add_real(h);
+            self.f.rhs_t(t, &mut xh);
+            Zip::from(jac.column_mut(j))
+                .and(&xh)
+                .and(&fx)
+                .for_each(|j, &xh, &fx| *j = (xh - fx).div_real(h));
+        }
+        jac
+    }
+
+    /// Change the step size of the divided differences by `factor`
+    fn change_d(&mut self, factor: <F::Scalar as Scalar>::Real) {
+        let order = self.order;
+        let factor = factor.to_f64().unwrap();
+        let ru = compute_r(order, factor).dot(&compute_r(order, 1.0));
+        let d = self.d.slice(s![..=order, ..]).to_owned();
+        for i in 0..=order {
+            let mut row = self.d.row_mut(i);
+            row.fill(F::Scalar::zero());
+            for k in 0..=order {
+                let c = F::Scalar::real(ru[(k, i)]);
+                row.zip_mut_with(&d.row(k), |r, d| *r += d.mul_real(c));
+            }
+        }
+    }
+
+    /// Discard the history and restart from the 1st order formula at `x`
+    fn reset(&mut self, x: &Array1<F::Scalar>) {
+        self.order = 1;
+        self.n_equal_steps = 0;
+        self.d = Array2::zeros((BDF_MAX_ORDER + 3, x.len()));
+        self.d.row_mut(0).assign(x);
+        let mut fx = x.clone();
+        self.f.rhs_t(self.t, &mut fx);
+        let dt = self.dt;
+        self.d
+            .row_mut(1)
+            .zip_mut_with(&fx, |d, f| *d = f.mul_real(dt));
+        self.lu = None;
+    }
+
+    /// Solve the implicit equation by the simplified Newton iteration
+    ///
+    /// Returns `None` if the iteration does not converge.
+    fn solve_newton(
+        &mut self,
+        t_new: <F::Scalar as Scalar>::Real,
+        y_predict: &Array1<F::Scalar>,
+        c: <F::Scalar as Scalar>::Real,
+        psi: &Array1<F::Scalar>,
+        scale: &Array1<<F::Scalar as Scalar>::Real>,
+    ) -> Option<NewtonResult<F::Scalar>> {
+        let one = F::Scalar::real(1.0);
+        let tol = Float::max(
+            F::Scalar::real(10.0) * <F::Scalar as Scalar>::Real::epsilon() / self.rtol,
+            Float::min(F::Scalar::real(0.03), Float::sqrt(self.rtol)),
+        );
+        let lu = self.lu.as_ref()?;
+        let mut y = y_predict.clone();
+        let mut d = Array1::zeros(y.len());
+        let mut dy_norm_old: Option<<F::Scalar as Scalar>::Real> = None;
+        for k in 0..BDF_NEWTON_MAXITER {
+            let mut fy = y.clone();
+            self.f.rhs_t(t_new, &mut fy);
+            if !fy.iter().all(|v| v.re().is_finite() && v.im().is_finite()) {
+                return None;
+            }
+            Zip::from(&mut fy)
+                .and(psi)
+                .and(&d)
+                .for_each(|f, &psi, &d| *f = f.mul_real(c) - psi - d);
+            let dy = lu.solve_into(fy).ok()?;
+            let dy_norm = rms_norm(&dy, scale);
+            let rate = dy_norm_old.map(|old| dy_norm / old);
+            if let Some(rate) = rate {
+                let remain = F::Scalar::real((BDF_NEWTON_MAXITER - k) as f64);
+                if rate >= one || Float::powf(rate, remain) / (one - rate) * dy_norm > tol {
+                    return None;
+                }
+            }
+            y += &dy;
+            d += &dy;
+            let converged = match rate {
+                Some(rate) => rate / (one - rate) * dy_norm < tol,
+                None => dy_norm.is_zero(),
+            };
+            if converged {
+                return Some((y, d, k + 1));
+            }
+            dy_norm_old = Some(dy_norm);
+        }
+        None
+    }
+}
+
+impl<F> TimeStep for BDF<F>
+where
+    F: NonAutonomousExplicit<Dim = Ix1>,
+    F::Scalar: Lapack,
+{
+    type Time = <F::Scalar as Scalar>::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        if self.y.is_some() {
+            self.change_d(dt / self.dt);
+            self.n_equal_steps = 0;
+            self.lu = None;
+        }
+        self.dt = dt;
+    }
+}
+
+impl<F> CurrentTime for BDF<F>
+where
+    F: NonAutonomousExplicit<Dim = Ix1>,
+    F::Scalar: Lapack,
+{
+    fn get_time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+        self.y = None;
+    }
+}
+
+impl<F> Scheme for BDF<F>
+where
+    F: NonAutonomousExplicit<Dim = Ix1>,
+    F::Scalar: Lapack,
+{
+    type Core = F;
+    fn new(f: F, dt: Self::Time) -> Self {
+        let n = f.model_size();
+        let tol = F::Scalar::real(1e-6);
+        BDF {
+            f,
+            t: F::Scalar::real(0.0),
+            dt,
+            last_dt: dt,
+            atol: tol,
+            rtol: tol,
+            order: 1,
+            n_equal_steps: 0,
+            rejected: 0,
+            d: Array2::zeros((BDF_MAX_ORDER + 3, n)),
+            y: None,
+            jac: None,
+            jac_current: false,
+            lu: None,
+            jacobian: None,
+        }
+    }
+    fn core(&self) -> &Self::Core {
+        &self.f
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.f
+    }
+}
+
+impl<F> ModelSpec for BDF<F>
+where
+    F: NonAutonomousExplicit<Dim = Ix1>,
+    F::Scalar: Lapack,
+{
+    type Scalar = F::Scalar;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        self.f.model_size()
+    }
+}
+
+impl<F> BDF<F>
+where
+    F: NonAutonomousExplicit<Dim = Ix1>,
+    F::Scalar: Lapack,
+{
+    /// Proceed one accepted step as [TimeEvolution::iterate]
+    ///
+    /// Returns [Error::StepUnderflow] if the step size underflows to zero,
+    /// where `x`, the time and the step size are left unchanged, and the history is discarded.
+    pub fn try_iterate<'a, S>(
+        &mut self,
+        x: &'a mut ArrayBase<S, Ix1>,
+    ) -> Result<&'a mut ArrayBase<S, Ix1>>
+    where
+        S: DataMut<Elem = F::Scalar>,
+    {
+        let real = F::Scalar::real;
+        let one = real(1.0);
+        let x0 = x.to_owned();
+        let dt0 = self.dt;
+        if self.y.as_ref() != Some(&x0) {
+            self.reset(&x0);
+        }
+        // gamma_k = sum_{j=1}^k 1/j, and error constants 1/(k+1) of BDF
+        let gamma = |k: usize| real((1..=k).map(|j| 1.0 / j as f64).sum::<f64>());
+        let error_const = |k: usize| real(1.0 / (k + 1) as f64);
+
+        let (y_new, d, n_iter) = loop {
+            let order = self.order;
+            let dt = self.dt;
+            if dt <= <F::Scalar as Scalar>::Real::zero() {
+                self.dt = dt0;
+                self.y = None;
+                return Err(Error::StepUnderflow);
+            }
+            let t_new = self.t + dt;
+            let y_predict = self.d.slice(s![..=order, ..]).sum_axis(Axis(0));
+            let scale = y_predict.mapv(|y| self.atol + self.rtol * y.abs());
+            let mut psi = Array1::zeros(x0.len());
+            for k in 1..=order {
+                let g = gamma(k) / gamma(order);
+                psi.zip_mut_with(&self.d.row(k), |p, d| *p += d.mul_real(g));
+            }
+            let c = dt / gamma(order);
+
+            let result = loop {
+                if self.lu.is_none() {
+                    if self.jac.is_none() {
+                        self.jac = Some(self.eval_jacobian(t_new, &y_predict));
+                        self.jac_current = true;
+                    }
+                    let mut m = self.jac.as_ref().unwrap().mapv(|j| -j.mul_real(c));
+                    for i in 0..m.nrows() {
+                        m[(i, i)] += F::Scalar::one();
+                    }
+                    self.lu = m.factorize_into().ok();
+                }
+                let result = self.solve_newton(t_new, &y_predict, c, &psi, &scale);
+                if result.is_some() || self.jac_current {
+                    break result;
+                }
+                self.jac = None;
+                self.lu = None;
+            };
+            let (y_new, d, n_iter) = match result {
+                Some(result) => result,
+                None => {
+                    // Newton iteration does not converge
+                    self.rejected += 1;
+                    self.change_d(real(0.5));
+                    self.dt *= real(0.5);
+                    self.n_equal_steps = 0;
+                    self.lu = None;
+                    continue;
+                }
+            };
+            let safety = real(0.9 * (2 * BDF_NEWTON_MAXITER + 1) as f64)
+                / real((2 * BDF_NEWTON_MAXITER + n_iter) as f64);
+            let scale = y_new.mapv(|y| self.atol + self.rtol * y.abs());
+            let error_norm = rms_norm(&d, &scale) * error_const(order);
+            if error_norm > one {
+                self.rejected += 1;
+                let factor = Float::max(
+                    real(BDF_MIN_FACTOR),
+                    safety * Float::powf(error_norm, -one / real((order + 1) as f64)),
+                );
+                self.change_d(factor);
+                self.dt *= factor;
+                self.n_equal_steps = 0;
+                self.lu = None;
+                continue;
+            }
+            break (y_new, d, n_iter);
+        };
+
+        // accept the step
+        let order = self.order;
+        self.n_equal_steps += 1;
+        self.last_dt = self.dt;
+        self.t += self.dt;
+        self.jac_current = false;
+        {
+            let dk1 = self.d.row(order + 1).to_owned();
+            self.d.row_mut(order + 2).assign(&(&d - &dk1));
+            self.d.row_mut(order + 1).assign(&d);
+            for i in (0..=order).rev() {
+                let next = self.d.row(i + 1).to_owned();
+                self.d.row_mut(i).zip_mut_with(&next, |a, b| *a += *b);
+            }
+        }
+        x.assign(&y_new);
+        self.y = Some(y_new);
+
+        if self.n_equal_steps < order + 1 {
+            return Ok(x);
+        }
+        // select the order and the step size of the next step
+        let scale = self
+            .y
+            .as_ref()
+            .unwrap()
+            .mapv(|y| self.atol + self.rtol * y.abs());
+        let safety = real(0.9 * (2 * BDF_NEWTON_MAXITER + 1) as f64)
+            / real((2 * BDF_NEWTON_MAXITER + n_iter) as f64);
+        let norm = |k: usize| rms_norm(&self.d.row(k).to_owned(), &scale);
+        let error_norm = rms_norm(&d, &scale) * error_const(order);
+        let error_m = if order > 1 {
+            norm(order) * error_const(order - 1)
+        } else {
+            <F::Scalar as Scalar>::Real::infinity()
+        };
+        let error_p = if order < BDF_MAX_ORDER {
+            norm(order + 2) * error_const(order + 1)
+        } else {
+            <F::Scalar as Scalar>::Real::infinity()
+        };
+        let factor =
+            |e: <F::Scalar as Scalar>::Real, k: usize| Float::powf(e, -one / real(k as f64));
+        let candidates = [
+            (order - 1, factor(error_m, order)),
+            (order, factor(error_norm, order + 1)),
+            (order + 1, factor(error_p, order + 2)),
+        ];
+        let (new_order, max_factor) =
+            candidates
+                .iter()
+                .fold(candidates[1], |acc, &c| if c.1 > acc.1 { c } else { acc });
+        self.order = new_order;
+        let factor = Float::min(real(BDF_MAX_FACTOR), safety * max_factor);
+        self.change_d(factor);
+        self.dt *= factor;
+        self.n_equal_steps = 0;
+        self.lu = None;
+        Ok(x)
+    }
+}
+
+impl<F> TimeEvolution for BDF<F>
+where
+    F: NonAutonomousExplicit<Dim = Ix1>,
+    F::Scalar: Lapack,
+{
+    fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        self.try_iterate(x)
+            .unwrap_or_else(|e| panic!("{} in BDF", e))
+    }
+}
@@ -32,7 +32,7 @@
 //! by ignoring phase-space volume contraction,
 //! or stiff systems can be integrated with explicit schemes with very small time steps.
 //!
-//! This crate introduces traits for each abstractions, e.g. [Explicit] or [SemiImplicit],
+//! This crate introduces traits for each abstractions, e.g. [Explicit], [SemiImplicit] or [SeparableHamiltonian],
 //! which are implemented for each equations corresponds to ODE itself, e.g. [ode::Lorenz63].
 //! Schemes, e.g. [explicit::Euler], use this traits as type-bound.
 //!
@@ -62,11 +62,18 @@
 //!
 
 pub mod adaptor;
+pub mod error;
+pub mod event;
 pub mod explicit;
+pub mod implicit;
 pub mod lyapunov;
 pub mod ode;
 pub mod pde;
 pub mod semi_implicit;
+pub mod symplectic;
 
+mod complex;
+mod hermite;
 mod traits;
+pub use error::Error;
 pub use traits::*;
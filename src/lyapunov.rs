@@ -2,28 +2,38 @@
 
 use ndarray::*;
 use ndarray_linalg::*;
-use num_traits::{Float, FromPrimitive, One};
+use num_traits::{Float, FromPrimitive, One, ToPrimitive, Zero};
+use std::collections::VecDeque;
 
-use crate::traits::*;
+use crate::{complex::imaginary_unit, error::*, traits::*};
 
 /// Jacobian operator using numerical-differentiation
+///
+/// The scheme is advanced by a step from `x` on creation,
+/// and every probe is started from the same time by [CurrentTime::set_time]
+/// so that the difference quotient does not depend on the time.
+/// Multistep schemes, e.g. [crate::implicit::BDF], restart at each probe since the state is moved.
 pub struct Jacobian<'jac, A, D, TEO>
 where
     A: Scalar + Lapack,
     D: Dimension,
-    TEO: 'jac + TimeEvolution<Scalar = A, Dim = D>,
+    TEO: 'jac + TimeEvolution<Scalar = A, Dim = D> + CurrentTime,
 {
     f: &'jac mut TEO,
     x: Array<A, D>,
     fx: Array<A, D>,
     alpha: A::Real,
+    /// Time at `x`
+    t0: TEO::Time,
+    /// Time at `fx`
+    t1: TEO::Time,
 }
 
 pub trait LinearApprox<A, D, TEO>
 where
     A: Scalar + Lapack,
     D: Dimension,
-    TEO: TimeEvolution<Scalar = A, Dim = D>,
+    TEO: TimeEvolution<Scalar = A, Dim = D> + CurrentTime,
 {
     fn lin_approx<'jac>(
         &'jac mut self,
@@ -38,7 +48,7 @@ impl<A, D, TEO> LinearApprox<A, D, TEO> for TEO
 where
     A: Scalar + Lapack,
     D: Dimension,
-    TEO: TimeEvolution<Scalar = A, Dim = D>,
+    TEO: TimeEvolution<Scalar = A, Dim = D> + CurrentTime,
 {
     fn lin_approx<'jac>(&'jac mut self, x: Array<A, D>, alpha: A::Real) -> Jacobian<'jac, A, D, TEO>
     where
@@ -52,15 +62,29 @@ impl<'jac, A, D, TEO> Jacobian<'jac, A, D, TEO>
 where
     A: Scalar + Lapack,
     D: Dimension,
-    TEO: TimeEvolution<Scalar = A, Dim = D>,
+    TEO: TimeEvolution<Scalar = A, Dim = D> + CurrentTime,
 {
     pub fn new(f: &'jac mut TEO, x: Array<A, D>, alpha: A::Real) -> Jacobian<'jac, A, D, TEO>
     where
         TEO: 'jac,
     {
+        let t0 = f.get_time();
         let mut fx = x.clone();
         f.iterate(&mut fx);
-        Jacobian { f, x, fx, alpha }
+        let t1 = f.get_time();
+        Jacobian {
+            f,
+            x,
+            fx,
+            alpha,
+            t0,
+            t1,
+        }
+    }
+
+    /// The state advanced by a step from `x`
+    pub fn image(&self) -> &Array<A, D> {
+        &self.fx
     }
 
     pub fn apply(&mut self, mut dx: Array<A, D>) -> Array<A, D> {
@@ -77,7 +101,9 @@ where
         Zip::from(&mut *dx).and(&self.x).for_each(|dx, &x| {
             *dx = x + dx.mul_real(n);
         });
+        self.f.set_time(self.t0);
         let x_dx = self.f.iterate(dx);
+        self.f.set_time(self.t1);
         Zip::from(&mut *x_dx).and(&self.fx).for_each(|x_dx, &fx| {
             *x_dx = (*x_dx - fx).div_real(n);
         });
@@ -108,24 +134,211 @@ where
     }
 }
 
-/// Calculate all Lyapunov exponents
+/// Dimension of the model space as a real linear space
+///
+/// The tangent space of a complex model space $\mathbb{C}^N$ is regarded as $\mathbb{R}^{2N}$
+/// since the time-evolution operator is not holomorphic in general,
+/// e.g. spectral PDE whose state is the Fourier coefficients of a real field.
+pub fn real_dimension<A: Scalar>(n: usize) -> usize {
+    if imaginary_unit::<A>().is_some() {
+        2 * n
+    } else {
+        n
+    }
+}
+
+/// Flatten a tangent vector into a real vector
+///
+/// Complex elements are stored in the order of `[re, im, re, im, ...]`.
+pub fn flatten<A, S, D>(dx: &ArrayBase<S, D>) -> Array1<A::Real>
+where
+    A: Scalar,
+    S: Data<Elem = A>,
+    D: Dimension,
+{
+    if imaginary_unit::<A>().is_some() {
+        dx.iter().flat_map(|x| [x.re(), x.im()]).collect()
+    } else {
+        dx.iter().map(|x| x.re()).collect()
+    }
+}
+
+/// Inverse of [flatten], i.e. set the elements of `dx` from a real vector `v`
+pub fn unflatten<A, S, Sv, D>(v: &ArrayBase<Sv, Ix1>, dx: &mut ArrayBase<S, D>)
+where
+    A: Scalar,
+    S: DataMut<Elem = A>,
+    Sv: Data<Elem = A::Real>,
+    D: Dimension,
+{
+    match imaginary_unit::<A>() {
+        Some(i) => {
+            for (dx, v) in dx.iter_mut().zip(v.exact_chunks(2)) {
+                *dx = A::from_real(v[0]) + i.mul_real(v[1]);
+            }
+        }
+        None => {
+            for (dx, &v) in dx.iter_mut().zip(v.iter()) {
+                *dx = A::from_real(v);
+            }
+        }
+    }
+}
+
+/// Calculate the leading `k` Lyapunov exponents
 ///
 /// This is an example usage of `Series` itertor, with which you can write more flexible procedure.
-pub fn exponents<A, TEO>(teo: TEO, x: Array1<A>, alpha: A::Real, duration: usize) -> Array1<A::Real>
+/// All exponents are calculated if `k` is the [real_dimension] of the model space.
+pub fn exponents<A, TEO>(
+    teo: TEO,
+    x: Array<A, TEO::Dim>,
+    alpha: A::Real,
+    k: usize,
+    duration: usize,
+) -> Array1<A::Real>
 where
     A: Scalar + Lapack,
-    TEO: TimeEvolution<Scalar = A, Dim = Ix1> + TimeStep<Time = A::Real>,
+    A::Real: Lapack,
+    TEO: TimeEvolution<Scalar = A> + CurrentTime<Time = A::Real>,
 {
-    let n = teo.model_size();
-    let dur = teo.get_dt() * TEO::Time::from_usize(duration).unwrap();
-    Series::new(teo, x, alpha)
-        .map(|(_x, _q, r)| r.diag().map(|x| Scalar::ln(x.abs())))
-        .skip(duration / 10)
-        .take(duration)
-        .fold(ArrayBase::zeros(n), |mut x, y| {
-            azip!((x in &mut x, &y in &y) *x += y/dur );
-            x
-        })
+    try_exponents(teo, x, alpha, k, duration).expect("Failed to calculate Lyapunov exponents")
+}
+
+/// Fallible version of [exponents]
+pub fn try_exponents<A, TEO>(
+    teo: TEO,
+    x: Array<A, TEO::Dim>,
+    alpha: A::Real,
+    k: usize,
+    duration: usize,
+) -> Result<Array1<A::Real>>
+where
+    A: Scalar + Lapack,
+    A::Real: Lapack,
+    TEO: TimeEvolution<Scalar = A> + CurrentTime<Time = A::Real>,
+{
+    let dt = teo.get_dt();
+    mean_exponents(Series::new(teo, x, alpha, k), dt, duration)
+}
+
+/// Calculate the leading `k` Lyapunov exponents using the tangent-linear operator
+///
+/// Same as [exponents], but tangent vectors are evolved by [TangentEvolution]
+/// without numerical differentiation.
+pub fn tangent_exponents<A, TEO>(
+    teo: TEO,
+    x: Array<A, TEO::Dim>,
+    k: usize,
+    duration: usize,
+) -> Array1<A::Real>
+where
+    A: Scalar + Lapack,
+    A::Real: Lapack,
+    TEO: TangentEvolution<Scalar = A> + TimeStep<Time = A::Real>,
+    <TEO::Dim as Dimension>::Larger: RemoveAxis + Dimension<Smaller = TEO::Dim>,
+{
+    let dt = teo.get_dt();
+    mean_exponents(Series::tangent(teo, x, k), dt, duration)
+        .expect("Failed to calculate Lyapunov exponents")
+}
+
+fn mean_exponents<A, TEO, L>(
+    mut series: Series<A, TEO, L>,
+    dt: A::Real,
+    duration: usize,
+) -> Result<Array1<A::Real>>
+where
+    A: Scalar + Lapack,
+    A::Real: Lapack,
+    TEO: TimeEvolution<Scalar = A>,
+    L: Linearization<TEO>,
+{
+    let n = series.q.ncols();
+    let dur = dt * A::Real::from_usize(duration).unwrap();
+    for _ in 0..duration / 10 {
+        series.try_next()?;
+    }
+    let mut l = Array::zeros(n);
+    for _ in 0..duration {
+        let (_x, _q, r) = series.try_next()?;
+        azip!((l in &mut l, &r in &r.diag()) *l += Float::ln(Float::abs(r)) / dur);
+    }
+    Ok(l)
+}
+
+/// How to evolve tangent vectors in [Series]
+pub trait Linearization<TEO: TimeEvolution> {
+    /// Evolve the state `x` by one step, and tangent vectors at `x`
+    /// stored in the columns of `q` as [flatten]-ed real vectors
+    fn evolve(
+        &self,
+        teo: &mut TEO,
+        x: &mut Array<TEO::Scalar, TEO::Dim>,
+        q: &mut Array2<<TEO::Scalar as Scalar>::Real>,
+    );
+}
+
+/// Numerical differentiation by [Jacobian] with a finite step `alpha`
+#[derive(Debug, Clone, Copy)]
+pub struct FiniteDifference<R> {
+    pub alpha: R,
+}
+
+impl<A, TEO> Linearization<TEO> for FiniteDifference<A::Real>
+where
+    A: Scalar + Lapack,
+    TEO: TimeEvolution<Scalar = A> + CurrentTime,
+{
+    fn evolve(&self, teo: &mut TEO, x: &mut Array<A, TEO::Dim>, q: &mut Array2<A::Real>) {
+        let mut jacobian = teo.lin_approx(x.to_owned(), self.alpha);
+        let mut dx = x.to_owned();
+        for mut col in q.axis_iter_mut(Axis(1)) {
+            unflatten(&col, &mut dx);
+            jacobian.apply_inplace(&mut dx);
+            col.assign(&flatten(&dx));
+        }
+        x.assign(jacobian.image());
+    }
+}
+
+/// Exact tangent-linear operator given by [TangentEvolution]
+#[derive(Debug, Clone, Copy)]
+pub struct Tangent;
+
+impl<TEO> Linearization<TEO> for Tangent
+where
+    TEO: TangentEvolution,
+    <TEO::Dim as Dimension>::Larger: RemoveAxis + Dimension<Smaller = TEO::Dim>,
+{
+    fn evolve(
+        &self,
+        teo: &mut TEO,
+        x: &mut Array<TEO::Scalar, TEO::Dim>,
+        q: &mut Array2<<TEO::Scalar as Scalar>::Real>,
+    ) {
+        let last = Axis(x.ndim());
+        let mut shape = x.raw_dim().insert_axis(last);
+        shape[x.ndim()] = q.ncols();
+        let mut dx = Array::zeros(shape);
+        for (mut dx, col) in dx.axis_iter_mut(last).zip(q.axis_iter(Axis(1))) {
+            unflatten(&col, &mut dx);
+        }
+        teo.iterate_tangent(x, &mut dx);
+        for (dx, mut col) in dx.axis_iter(last).zip(q.axis_iter_mut(Axis(1))) {
+            col.assign(&flatten(&dx));
+        }
+    }
+}
+
+/// The first `k` columns of the identity matrix of size `n`
+fn initial_vectors<A: Scalar>(n: usize, k: usize) -> Array2<A> {
+    assert!(
+        0 < k && k <= n,
+        "Number of tangent vectors must be in 1..={}",
+        n
+    );
+    // not sliced from `Array::eye(n)`, which leaves zero strides for `k = 1` rejected by LAPACK
+    Array::from_shape_fn((n, k), |(i, j)| if i == j { A::one() } else { A::zero() })
 }
 
 /// An iterator for successive QR-decomposition in Lyapunov analysis
@@ -134,82 +347,572 @@ where
 /// The `Item` of the iterator is `(x, Q, R)` where `x` is the state vector.
 /// Be sure that each column of `Q` belongs to the tangent space at `x`,
 /// and `R` is a map from the previous tangent space (i.e. at `F^{-1}(x)`) to the space spand by `Q`.
-pub struct Series<A, TEO>
+///
+/// The model space may be multi-dimensional or complex.
+/// The columns of `Q` are the tangent vectors [flatten]-ed into real vectors.
+/// Only `k` tangent vectors are evolved to calculate the leading `k` exponents,
+/// i.e. `Q` is a `n x k` matrix where `n` is the [real_dimension] of the model space,
+/// and `R` is a `k x k` upper triangular matrix.
+///
+/// Tangent vectors are evolved by numerical differentiation if created by [Series::new],
+/// or by the tangent-linear operator if created by [Series::tangent].
+pub struct Series<A, TEO, L = FiniteDifference<<A as Scalar>::Real>>
 where
     A: Scalar + Lapack,
-    TEO: TimeEvolution<Scalar = A, Dim = Ix1>,
+    A::Real: Lapack,
+    TEO: TimeEvolution<Scalar = A>,
 {
     teo: TEO,
-    x: Array1<A>,
-    q: Array2<A>,
-    alpha: A::Real,
+    x: Array<A, TEO::Dim>,
+    q: Array2<A::Real>,
+    linearization: L,
 }
 
 impl<A, TEO> Series<A, TEO>
 where
     A: Scalar + Lapack,
-    TEO: TimeEvolution<Scalar = A, Dim = Ix1>,
+    A::Real: Lapack,
+    TEO: TimeEvolution<Scalar = A> + CurrentTime,
+{
+    pub fn new(teo: TEO, x: Array<A, TEO::Dim>, alpha: A::Real, k: usize) -> Self {
+        let q = initial_vectors(real_dimension::<A>(x.len()), k);
+        Series {
+            teo,
+            x,
+            q,
+            linearization: FiniteDifference { alpha },
+        }
+    }
+}
+
+impl<A, TEO> Series<A, TEO, Tangent>
+where
+    A: Scalar + Lapack,
+    A::Real: Lapack,
+    TEO: TangentEvolution<Scalar = A>,
+    <TEO::Dim as Dimension>::Larger: RemoveAxis + Dimension<Smaller = TEO::Dim>,
 {
-    pub fn new(teo: TEO, x: Array1<A>, alpha: A::Real) -> Self {
-        let q = Array::eye(teo.model_size());
-        Series { teo, x, q, alpha }
+    pub fn tangent(teo: TEO, x: Array<A, TEO::Dim>, k: usize) -> Self {
+        let q = initial_vectors(real_dimension::<A>(x.len()), k);
+        Series {
+            teo,
+            x,
+            q,
+            linearization: Tangent,
+        }
     }
 }
 
-impl<A, TEO> Iterator for Series<A, TEO>
+impl<A, TEO, L> Series<A, TEO, L>
 where
     A: Scalar + Lapack,
-    TEO: TimeEvolution<Scalar = A, Dim = Ix1>,
+    A::Real: Lapack,
+    TEO: TimeEvolution<Scalar = A>,
+    L: Linearization<TEO>,
 {
-    type Item = (Array1<A>, Array2<A>, Array2<A>);
+    /// Fallible version of [Iterator::next]
+    ///
+    /// This returns an error if the state or tangent vectors become non-finite,
+    /// or the QR decomposition fails.
+    #[allow(clippy::type_complexity)]
+    pub fn try_next(&mut self) -> Result<(Array<A, TEO::Dim>, Array2<A::Real>, Array2<A::Real>)> {
+        self.linearization
+            .evolve(&mut self.teo, &mut self.x, &mut self.q);
+        if !is_finite(&self.x) || !is_finite(&self.q) {
+            return Err(Error::NonFinite);
+        }
+        let (q, r) = self.q.qr()?;
+        self.q = q;
+        Ok((self.x.to_owned(), self.q.to_owned(), r))
+    }
+}
+
+impl<A, TEO, L> Iterator for Series<A, TEO, L>
+where
+    A: Scalar + Lapack,
+    A::Real: Lapack,
+    TEO: TimeEvolution<Scalar = A>,
+    L: Linearization<TEO>,
+{
+    type Item = (Array<A, TEO::Dim>, Array2<A::Real>, Array2<A::Real>);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let q = self
-            .teo
-            .lin_approx(self.x.to_owned(), self.alpha)
-            .apply_multi_inplace(&mut self.q);
-        let (q, r) = q.qr_square_inplace().unwrap();
-        self.teo.iterate(&mut self.x);
-        Some((self.x.to_owned(), q.to_owned(), r))
+        Some(self.try_next().expect("Failed to evolve tangent vectors"))
+    }
+}
+
+fn is_finite<A: Scalar, S: Data<Elem = A>, D: Dimension>(a: &ArrayBase<S, D>) -> bool {
+    a.iter().all(|a| a.re().is_finite() && a.im().is_finite())
+}
+
+impl<A, TEO, L> Series<A, TEO, L>
+where
+    A: Scalar + Lapack,
+    A::Real: Lapack,
+    TEO: TimeEvolution<Scalar = A> + TimeStep<Time = A::Real>,
+    L: Linearization<TEO>,
+{
+    /// Convert into an iterator of local and finite-time Lyapunov exponents
+    ///
+    /// - `transient`: number of steps skipped at first for the convergence of `Q`
+    /// - `window`: number of steps averaged for the finite-time Lyapunov exponents (FTLE)
+    pub fn finite_time(self, transient: usize, window: usize) -> FiniteTimeExponents<A, TEO, L> {
+        FiniteTimeExponents::new(self, transient, window)
     }
 }
 
-fn clv_backward<A: Scalar + Lapack>(c: &Array2<A>, r: &Array2<A>) -> (Array2<A>, Array1<A::Real>) {
-    let cd = r
-        .solve_triangular(UPLO::Upper, ::ndarray_linalg::Diag::NonUnit, c)
-        .expect("Failed to solve R");
+/// Item of [FiniteTimeExponents]
+#[derive(Debug, Clone)]
+pub struct LocalExponents<A: Scalar, D: Dimension> {
+    /// State after the step
+    pub x: Array<A, D>,
+    /// Local growth rates of the step, i.e. `ln|R_ii| / dt`
+    pub local: Array1<A::Real>,
+    /// Finite-time Lyapunov exponents averaged over the last `window` steps,
+    /// `None` until `window` steps are passed after the transient
+    pub ftle: Option<Array1<A::Real>>,
+    /// Running mean of the local growth rates after the transient,
+    /// which converges to the Lyapunov exponents
+    pub mean: Array1<A::Real>,
+    /// Maximum difference of `mean` from that of `window` steps before,
+    /// `None` until `2 * window` steps are passed after the transient
+    pub drift: Option<A::Real>,
+    /// Standard error of `mean` estimated from the FTLEs of non-overlapping windows,
+    /// `None` until `2 * window` steps are passed after the transient
+    pub stderr: Option<Array1<A::Real>>,
+}
+
+/// An iterator adaptor of [Series] for local and finite-time Lyapunov exponents (FTLE)
+///
+/// This yields [LocalExponents] for each step after the transient.
+/// The `drift` and `stderr` of the running mean are diagnostics
+/// for the convergence of the Lyapunov exponents.
+/// Since the local exponents fluctuate chaotically, `drift` decays faster than the actual error,
+/// and thus `stderr` is more reliable.
+pub struct FiniteTimeExponents<A, TEO, L = FiniteDifference<<A as Scalar>::Real>>
+where
+    A: Scalar + Lapack,
+    A::Real: Lapack,
+    TEO: TimeEvolution<Scalar = A>,
+{
+    series: Series<A, TEO, L>,
+    transient: usize,
+    window: usize,
+    /// Local growth rates of the last `window` steps
+    history: VecDeque<Array1<A::Real>>,
+    window_sum: Array1<A::Real>,
+    total_sum: Array1<A::Real>,
+    steps: usize,
+    /// Sums of FTLEs of non-overlapping windows and of their squares
+    block_sum: Array1<A::Real>,
+    block_square_sum: Array1<A::Real>,
+    stderr: Option<Array1<A::Real>>,
+}
+
+impl<A, TEO, L> FiniteTimeExponents<A, TEO, L>
+where
+    A: Scalar + Lapack,
+    A::Real: Lapack,
+    TEO: TimeEvolution<Scalar = A> + TimeStep<Time = A::Real>,
+    L: Linearization<TEO>,
+{
+    pub fn new(series: Series<A, TEO, L>, transient: usize, window: usize) -> Self {
+        assert!(window > 0, "window size must be positive");
+        let k = series.q.ncols();
+        FiniteTimeExponents {
+            series,
+            transient,
+            window,
+            history: VecDeque::with_capacity(window),
+            window_sum: Array::zeros(k),
+            total_sum: Array::zeros(k),
+            steps: 0,
+            block_sum: Array::zeros(k),
+            block_square_sum: Array::zeros(k),
+            stderr: None,
+        }
+    }
+
+    /// Fallible version of [Iterator::next]
+    ///
+    /// This returns an error if [Series::try_next] fails, including the steps of the transient.
+    pub fn try_next(&mut self) -> Result<LocalExponents<A, TEO::Dim>> {
+        while self.transient > 0 {
+            self.series.try_next()?;
+            self.transient -= 1;
+        }
+        let dt = self.series.teo.get_dt();
+        let (x, _q, r) = self.series.try_next()?;
+        let local = r.diag().mapv(|r| Float::ln(Float::abs(r)) / dt);
+
+        self.steps += 1;
+        self.total_sum += &local;
+        self.window_sum += &local;
+        self.history.push_back(local.clone());
+        if self.history.len() > self.window {
+            let old = self.history.pop_front().unwrap();
+            self.window_sum -= &old;
+        }
+
+        let real = |n: usize| A::Real::from_usize(n).unwrap();
+        let n = real(self.steps);
+        let mean = self.total_sum.mapv(|s| s / n);
+        let ftle = if self.history.len() == self.window {
+            let w = real(self.window);
+            Some(self.window_sum.mapv(|s| s / w))
+        } else {
+            None
+        };
+        let drift = if self.steps >= 2 * self.window {
+            // running mean of `window` steps before is `(total - window) / m`
+            let m = real(self.steps - self.window);
+            let mut drift = A::Real::zero();
+            Zip::from(&mean)
+                .and(&self.total_sum)
+                .and(&self.window_sum)
+                .for_each(|&mean, &total, &window| {
+                    drift = Float::max(drift, Float::abs(mean - (total - window) / m));
+                });
+            Some(drift)
+        } else {
+            None
+        };
+        if let (Some(ftle), 0) = (&ftle, self.steps % self.window) {
+            Zip::from(&mut self.block_sum)
+                .and(&mut self.block_square_sum)
+                .and(ftle)
+                .for_each(|sum, square_sum, &ftle| {
+                    *sum += ftle;
+                    *square_sum += ftle * ftle;
+                });
+            let blocks = self.steps / self.window;
+            if blocks >= 2 {
+                let b = real(blocks);
+                let stderr = Zip::from(&self.block_sum)
+                    .and(&self.block_square_sum)
+                    .map_collect(|&sum, &square_sum| {
+                        let var = (square_sum - sum * sum / b) / (b - A::Real::one());
+                        Float::sqrt(Float::max(var, A::Real::zero()) / b)
+                    });
+                self.stderr = Some(stderr);
+            }
+        }
+        Ok(LocalExponents {
+            x,
+            local,
+            ftle,
+            mean,
+            drift,
+            stderr: self.stderr.clone(),
+        })
+    }
+}
+
+impl<A, TEO, L> Iterator for FiniteTimeExponents<A, TEO, L>
+where
+    A: Scalar + Lapack,
+    A::Real: Lapack,
+    TEO: TimeEvolution<Scalar = A> + TimeStep<Time = A::Real>,
+    L: Linearization<TEO>,
+{
+    type Item = LocalExponents<A, TEO::Dim>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.try_next().expect("Failed to evolve tangent vectors"))
+    }
+}
+
+/// Kaplan-Yorke (Lyapunov) dimension of a Lyapunov spectrum
+///
+/// `D = j + (l_1 + ... + l_j) / |l_{j+1}|`, where `j` is the largest index
+/// that the sum of the leading `j` exponents is non-negative.
+/// The spectrum is sorted in descending order in this function.
+/// This returns the length of the spectrum if the sum of all exponents is non-negative,
+/// which means that the given spectrum is not enough, e.g. a partial spectrum.
+/// NaN is returned if the spectrum contains a non-finite value, e.g. after a divergent run.
+pub fn kaplan_yorke_dimension<R: Float>(l: &Array1<R>) -> R {
+    if l.iter().any(|l| !l.is_finite()) {
+        return R::nan();
+    }
+    let mut l = l.to_vec();
+    l.sort_by(|a, b| b.partial_cmp(a).unwrap());
+    let mut sum = R::zero();
+    for (j, &lj) in l.iter().enumerate() {
+        if sum + lj < R::zero() {
+            return R::from(j).unwrap() + sum / lj.abs();
+        }
+        sum = sum + lj;
+    }
+    R::from(l.len()).unwrap()
+}
+
+/// Kolmogorov-Sinai entropy estimated by Pesin's identity, i.e. the sum of positive exponents
+pub fn ks_entropy<R: Float>(l: &Array1<R>) -> R {
+    l.iter()
+        .filter(|l| **l > R::zero())
+        .fold(R::zero(), |sum, &l| sum + l)
+}
+
+/// Number of positive, zero, and negative exponents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignCount {
+    pub positive: usize,
+    pub zero: usize,
+    pub negative: usize,
+}
+
+/// Count the signs of exponents, where exponents within `tol` from zero are regarded as zero
+pub fn sign_count<R: Float>(l: &Array1<R>, tol: R) -> SignCount {
+    let mut count = SignCount {
+        positive: 0,
+        zero: 0,
+        negative: 0,
+    };
+    for &l in l {
+        if l.abs() <= tol {
+            count.zero += 1;
+        } else if l > R::zero() {
+            count.positive += 1;
+        } else {
+            count.negative += 1;
+        }
+    }
+    count
+}
+
+/// Lyapunov exponents calculated by [converged_exponents]
+#[derive(Debug, Clone)]
+pub struct Convergence<R> {
+    /// Running mean of the local exponents
+    pub exponents: Array1<R>,
+    /// Number of steps after the transient
+    pub steps: usize,
+    /// Standard error of `exponents`, see `stderr` of [LocalExponents]
+    pub stderr: Option<Array1<R>>,
+}
+
+/// Calculate Lyapunov exponents until the running mean stabilizes
+///
+/// The running mean of [FiniteTimeExponents] is calculated
+/// until its standard error estimated with the FTLEs over `window` steps
+/// becomes smaller than `tol` for all exponents.
+/// `window` should be longer than the correlation time of the FTLEs.
+/// This returns [Error::NotConverged] if it does not converge in `max_steps`,
+/// and the error of [FiniteTimeExponents::try_next] if the tangent vectors cannot be evolved.
+pub fn converged_exponents<A, TEO, L>(
+    series: Series<A, TEO, L>,
+    transient: usize,
+    window: usize,
+    tol: A::Real,
+    max_steps: usize,
+) -> Result<Convergence<A::Real>>
+where
+    A: Scalar + Lapack,
+    A::Real: Lapack,
+    TEO: TimeEvolution<Scalar = A> + TimeStep<Time = A::Real>,
+    L: Linearization<TEO>,
+{
+    let mut finite_time = series.finite_time(transient, window);
+    let mut stderr = None;
+    for steps in 1..=max_steps {
+        let l = finite_time.try_next()?;
+        if let Some(e) = &l.stderr {
+            if e.iter().all(|&e| e < tol) {
+                return Ok(Convergence {
+                    exponents: l.mean,
+                    steps,
+                    stderr: l.stderr,
+                });
+            }
+        }
+        stderr = l.stderr;
+    }
+    Err(Error::NotConverged {
+        stderr: stderr.map(|e| {
+            e.iter()
+                .fold(A::Real::zero(), |m, &e| Float::max(m, e))
+                .to_f64()
+                .unwrap()
+        }),
+    })
+}
+
+fn clv_backward<A: Scalar + Lapack>(
+    c: &Array2<A>,
+    r: &Array2<A>,
+) -> Result<(Array2<A>, Array1<A::Real>)> {
+    let cd = r.solve_triangular(UPLO::Upper, ::ndarray_linalg::Diag::NonUnit, c)?;
+    if !is_finite(&cd) {
+        return Err(Error::NonFinite);
+    }
     let (c, d) = normalize(cd, NormalizeAxis::Column);
     let f = Array::from(d).mapv_into(|x| A::Real::one() / x);
-    (c, f)
+    Ok((c, f))
 }
 
 /// Calculate the Covariant Lyapunov Vectors at once
 ///
 /// This function saves the time series of QR-decomposition, and consumes many memories.
+/// Use [CovariantVectors] for long time series.
+/// The leading `k` CLVs are [flatten]-ed into real vectors as the columns of `Q` of [Series].
+/// The result is same as the first `duration` items of [CovariantVectors]
+/// with `duration / 10` steps of forward and backward transients.
+#[allow(clippy::type_complexity)]
 pub fn vectors<A, TEO>(
     teo: TEO,
-    x: Array1<A>,
+    x: Array<A, TEO::Dim>,
     alpha: A::Real,
+    k: usize,
     duration: usize,
-) -> Vec<(Array1<A>, Array2<A>, Array1<A::Real>)>
-where
-    A: Scalar + Lapack,
-    TEO: TimeEvolution<Scalar = A, Dim = Ix1> + Clone,
-{
-    let n = teo.model_size();
-    let qr_series = Series::new(teo, x, alpha)
-        .skip(duration / 10)
-        .take(duration + duration / 10)
-        .collect::<Vec<_>>();
-    let clv_rev = qr_series
-        .into_iter()
-        .rev()
-        .scan(Array::eye(n), |c, (x, q, r)| {
-            let (c_now, f) = clv_backward(c, &r);
-            let v = q.dot(&c_now);
-            *c = c_now;
-            Some((x, v, f))
-        })
-        .collect::<Vec<_>>();
-    clv_rev.into_iter().skip(duration / 10).rev().collect()
+) -> Vec<(Array<A, TEO::Dim>, Array2<A::Real>, Array1<A::Real>)>
+where
+    A: Scalar + Lapack,
+    A::Real: Lapack,
+    TEO: TimeEvolution<Scalar = A> + CurrentTime + Clone,
+{
+    try_vectors(teo, x, alpha, k, duration).expect("Failed to calculate CLVs")
+}
+
+/// Fallible version of [vectors]
+///
+/// This fails if the tangent vectors degenerate, or the trajectory diverges.
+#[allow(clippy::type_complexity)]
+pub fn try_vectors<A, TEO>(
+    teo: TEO,
+    x: Array<A, TEO::Dim>,
+    alpha: A::Real,
+    k: usize,
+    duration: usize,
+) -> Result<Vec<(Array<A, TEO::Dim>, Array2<A::Real>, Array1<A::Real>)>>
+where
+    A: Scalar + Lapack,
+    A::Real: Lapack,
+    TEO: TimeEvolution<Scalar = A> + CurrentTime + Clone,
+{
+    let transient = duration / 10;
+    let mut series = Series::new(teo, x, alpha, k);
+    for _ in 0..transient {
+        series.try_next()?;
+    }
+    let qr_series = (0..duration + transient + 1)
+        .map(|_| series.try_next())
+        .collect::<Result<Vec<_>>>()?;
+    // C_{j-1} = R_j^{-1} C_j is the coefficients of CLVs in Q_{j-1}
+    let mut c = Array::eye(k);
+    let mut clv = Vec::with_capacity(duration);
+    for j in (1..qr_series.len()).rev() {
+        let (c_now, f) = clv_backward(&c, &qr_series[j].2)?;
+        c = c_now;
+        if j - 1 < duration {
+            let (x, q, _r) = &qr_series[j - 1];
+            clv.push((x.clone(), q.dot(&c), f));
+        }
+    }
+    clv.reverse();
+    Ok(clv)
+}
+
+/// Streaming calculation of the Covariant Lyapunov Vectors (CLV) by Ginelli's algorithm
+///
+/// The `Item` of the iterator is `(x, V, f)` in forward time order,
+/// where the columns of `V` are the normalized CLVs at the state `x`,
+/// and `f` is their local growth factors to the next step.
+/// CLVs are [flatten]-ed into real vectors as the columns of `Q` of [Series].
+///
+/// Ginelli's algorithm requires the backward iteration from the future,
+/// which is done block by block:
+/// the `(x, Q, R)` of [Series] are kept in a buffer of `block + backward + 1` entries,
+/// and CLVs of the first `block` entries are determined by the backward iteration
+/// from the end of the buffer through `backward` steps of transient.
+/// Thus the memory usage is bounded independently from the length of the time series.
+///
+/// Links
+/// ------
+/// - F. Ginelli et al., "Covariant Lyapunov vectors", J. Phys. A 46, 254005 (2013) <https://arxiv.org/abs/1212.3961>
+#[allow(clippy::type_complexity)]
+pub struct CovariantVectors<A, TEO, L = FiniteDifference<<A as Scalar>::Real>>
+where
+    A: Scalar + Lapack,
+    A::Real: Lapack,
+    TEO: TimeEvolution<Scalar = A>,
+{
+    series: Series<A, TEO, L>,
+    forward: usize,
+    backward: usize,
+    block: usize,
+    history: VecDeque<(Array<A, TEO::Dim>, Array2<A::Real>, Array2<A::Real>)>,
+    output: VecDeque<(Array<A, TEO::Dim>, Array2<A::Real>, Array1<A::Real>)>,
+}
+
+impl<A, TEO, L> CovariantVectors<A, TEO, L>
+where
+    A: Scalar + Lapack,
+    A::Real: Lapack,
+    TEO: TimeEvolution<Scalar = A>,
+    L: Linearization<TEO>,
+{
+    /// - `forward`: number of steps skipped at first for the convergence of `Q` of [Series]
+    /// - `backward`: number of steps of the backward iteration before the CLVs are output
+    /// - `block`: number of CLVs determined by a backward iteration
+    pub fn new(series: Series<A, TEO, L>, forward: usize, backward: usize, block: usize) -> Self {
+        assert!(block > 0, "block size must be positive");
+        CovariantVectors {
+            series,
+            forward,
+            backward,
+            block,
+            history: VecDeque::with_capacity(block + backward + 1),
+            output: VecDeque::with_capacity(block),
+        }
+    }
+
+    /// Determine the CLVs of the next block
+    fn backward_iteration(&mut self) -> Result<()> {
+        while self.forward > 0 {
+            self.series.try_next()?;
+            self.forward -= 1;
+        }
+        let len = self.block + self.backward + 1;
+        while self.history.len() < len {
+            let qr = self.series.try_next()?;
+            self.history.push_back(qr);
+        }
+        let n = self.series.q.ncols();
+        let mut c = Array::eye(n);
+        let mut output = Vec::with_capacity(self.block);
+        for j in (1..len).rev() {
+            let (c_now, f) = clv_backward(&c, &self.history[j].2)?;
+            c = c_now;
+            if j - 1 < self.block {
+                let (x, q, _r) = &self.history[j - 1];
+                output.push((x.clone(), q.dot(&c), f));
+            }
+        }
+        self.output.extend(output.into_iter().rev());
+        self.history.drain(..self.block);
+        Ok(())
+    }
+
+    /// Fallible version of [Iterator::next]
+    #[allow(clippy::type_complexity)]
+    pub fn try_next(&mut self) -> Result<(Array<A, TEO::Dim>, Array2<A::Real>, Array1<A::Real>)> {
+        if self.output.is_empty() {
+            self.backward_iteration()?;
+        }
+        Ok(self.output.pop_front().unwrap())
+    }
+}
+
+impl<A, TEO, L> Iterator for CovariantVectors<A, TEO, L>
+where
+    A: Scalar + Lapack,
+    A::Real: Lapack,
+    TEO: TimeEvolution<Scalar = A>,
+    L: Linearization<TEO>,
+{
+    type Item = (Array<A, TEO::Dim>, Array2<A::Real>, Array1<A::Real>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.try_next().expect("Failed to calculate CLV"))
+    }
 }
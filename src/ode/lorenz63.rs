@@ -82,3 +82,22 @@ impl SemiImplicit for Lorenz63 {
         Array::from(vec![-self.p, -1.0, -self.b])
     }
 }
+
+impl AnalyticJacobian for Lorenz63 {
+    fn jacobian<'a, S, Sx>(
+        &mut self,
+        v: &ArrayBase<Sx, Ix1>,
+        dv: &'a mut ArrayBase<S, Ix1>,
+    ) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+        Sx: Data<Elem = f64>,
+    {
+        let (x, y, z) = (v[0], v[1], v[2]);
+        let (dx, dy, dz) = (dv[0], dv[1], dv[2]);
+        dv[0] = self.p * (dy - dx);
+        dv[1] = (self.r - z) * dx - dy - x * dz;
+        dv[2] = y * dx + x * dy - self.b * dz;
+        dv
+    }
+}
@@ -52,3 +52,25 @@ impl Explicit for Lorenz96 {
         v
     }
 }
+
+impl AnalyticJacobian for Lorenz96 {
+    fn jacobian<'a, S, Sx>(
+        &mut self,
+        v: &ArrayBase<Sx, Ix1>,
+        dv: &'a mut ArrayBase<S, Ix1>,
+    ) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+        Sx: Data<Elem = f64>,
+    {
+        let n = v.len();
+        let dv0 = dv.to_owned();
+        for i in 0..n {
+            let p1 = (i + 1) % n;
+            let m1 = (i + n - 1) % n;
+            let m2 = (i + n - 2) % n;
+            dv[i] = (dv0[p1] - dv0[m2]) * v[m1] + (v[p1] - v[m2]) * dv0[m1] - dv0[i];
+        }
+        dv
+    }
+}
@@ -64,3 +64,22 @@ impl Explicit for Roessler {
         v
     }
 }
+
+impl AnalyticJacobian for Roessler {
+    fn jacobian<'a, S, Sx>(
+        &mut self,
+        v: &ArrayBase<Sx, Ix1>,
+        dv: &'a mut ArrayBase<S, Ix1>,
+    ) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+        Sx: Data<Elem = f64>,
+    {
+        let (x, z) = (v[0], v[2]);
+        let (dx, dy, dz) = (dv[0], dv[1], dv[2]);
+        dv[0] = -dy - dz;
+        dv[1] = dx + self.a * dy;
+        dv[2] = z * dx + (x - self.c) * dz;
+        dv
+    }
+}
@@ -0,0 +1,106 @@
+use fftw::types::c64;
+use ndarray::*;
+
+use super::{wavenumbers, CPairND};
+use crate::traits::*;
+
+#[cfg_attr(doc, katexit::katexit)]
+/// Complex Ginzburg-Landau equation with spectral method
+///
+/// $$
+/// \frac{\partial A}{\partial t} = A + (1 + i\alpha) \nabla^2 A - (1 + i\beta) |A|^2 A
+/// $$
+///
+/// where $A = A(x, t)$ is complex value field on the one- or two-dimensional periodic box.
+/// The state is the Fourier coefficients of $A$ in the layout of [CPairND](super::CPairND).
+/// The plane waves $A = \sqrt{1 - q^2} e^{i(qx - \omega t)}$ with $\omega = \alpha q^2 + \beta (1 - q^2)$
+/// are exact solutions, which are unstable if $1 + \alpha\beta < 0$ (Benjamin-Feir instability)
+/// and exhibit phase or defect turbulence.
+///
+/// Links
+/// -----
+/// - ["The world of the complex Ginzburg-Landau equation", I. S. Aranson and L. Kramer, Rev. Mod. Phys. 74, 99 (2002)](https://journals.aps.org/rmp/abstract/10.1103/RevModPhys.74.99)
+#[derive(Clone)]
+pub struct CGLE<D: Dimension> {
+    alpha: f64,
+    beta: f64,
+    k2: Array<f64, D>,
+    a: CPairND<D>,
+}
+
+impl<D: Dimension> ModelSpec for CGLE<D> {
+    type Scalar = c64;
+    type Dim = D;
+    fn model_size(&self) -> D::Pattern {
+        self.k2.dim()
+    }
+}
+
+impl CGLE<Ix1> {
+    /// - `n`: Number of grid points
+    /// - `length`: System size $L$
+    /// - `alpha`: Coefficient of the linear dispersion $\alpha$
+    /// - `beta`: Coefficient of the nonlinear dispersion $\beta$
+    pub fn new(n: usize, length: f64, alpha: f64, beta: f64) -> Self {
+        let k = wavenumbers(n, length);
+        CGLE {
+            alpha,
+            beta,
+            k2: &k * &k,
+            a: CPairND::new(n),
+        }
+    }
+}
+
+impl CGLE<Ix2> {
+    /// Two-dimensional version of [CGLE::new] on the box $[0, L_x] \times [0, L_y]$
+    /// with $(N_x, N_y)$ grid points
+    pub fn new_2d(n: (usize, usize), length: (f64, f64), alpha: f64, beta: f64) -> Self {
+        let kx = wavenumbers(n.0, length.0);
+        let ky = wavenumbers(n.1, length.1);
+        CGLE {
+            alpha,
+            beta,
+            k2: Array::from_shape_fn(n, |(i, j)| kx[i] * kx[i] + ky[j] * ky[j]),
+            a: CPairND::new(n),
+        }
+    }
+}
+
+impl<D: Dimension> CGLE<D> {
+    /// $|k|^2$ for each coefficient
+    pub fn k2(&self) -> &Array<f64, D> {
+        &self.k2
+    }
+
+    /// Mean squared amplitude $\langle |A|^2 \rangle$ averaged over the box
+    pub fn norm_sqr<S>(&self, a: &ArrayBase<S, D>) -> f64
+    where
+        S: Data<Elem = c64>,
+    {
+        a.iter().map(|a| a.norm_sqr()).sum()
+    }
+}
+
+impl<D: Dimension> SemiImplicit for CGLE<D> {
+    fn nlin<'a, S>(&mut self, af: &'a mut ArrayBase<S, D>) -> &'a mut ArrayBase<S, D>
+    where
+        S: DataMut<Elem = c64>,
+    {
+        let c = -c64::new(1.0, self.beta);
+        self.a.coeff_view_mut().assign(af);
+        self.a.backward();
+        for a in self.a.x.iter_mut() {
+            *a *= c * a.norm_sqr();
+        }
+        self.a.forward();
+        af.assign(&self.a.coeff_view());
+        af
+    }
+
+    fn diag(&self) -> Array<c64, D> {
+        let alpha = self.alpha;
+        self.k2
+            .mapv(|k2| c64::new(1.0, 0.0) - c64::new(1.0, alpha) * k2)
+    }
+}
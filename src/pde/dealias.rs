@@ -0,0 +1,96 @@
+use ndarray::*;
+
+#[cfg_attr(doc, katexit::katexit)]
+/// Dealiasing of the nonlinear terms evaluated in the real space
+///
+/// The product of two fields truncated at $|k| \le K$ has the modes up to $2K$,
+/// which are aliased into the resolved modes on the $N$-point grid.
+///
+/// - [Dealiasing::TwoThirds] truncates the product to the modes $|k| < N/3$ by Orszag's 2/3-rule
+/// - [Dealiasing::Padding] evaluates the product on the $3N/2$-point grid with zero-padding,
+///   which removes the aliasing of quadratic terms exactly.
+///   Cubic terms are evaluated on the $2N$-point grid, see [Dealiasing::grid_size_for].
+/// - [Dealiasing::Exponential] multiplies the smooth filter $\exp(-\alpha (k/k_{\max})^m)$
+///   where $k_{\max} = N/2$. [Dealiasing::hou_li] returns $\alpha = 36, m = 36$ proposed by Hou and Li.
+///
+/// Links
+/// -----
+/// - ["On the elimination of aliasing in finite-difference schemes by filtering high-wavenumber components", S. A. Orszag, J. Atmos. Sci. 28, 1074 (1971)](https://doi.org/10.1175/1520-0469(1971)028%3C1074:OTEOAI%3E2.0.CO;2)
+/// - ["Computing nearly singular solutions using pseudo-spectral methods", T. Y. Hou and R. Li, J. Comput. Phys. 226, 379 (2007)](https://doi.org/10.1016/j.jcp.2007.04.014)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Dealiasing {
+    #[default]
+    None,
+    TwoThirds,
+    Padding,
+    Exponential {
+        alpha: f64,
+        order: i32,
+    },
+}
+
+impl Dealiasing {
+    /// Exponential filter by Hou and Li
+    pub fn hou_li() -> Self {
+        Dealiasing::Exponential {
+            alpha: 36.0,
+            order: 36,
+        }
+    }
+
+    /// Number of grid points to evaluate the quadratic terms for the `n`-point model
+    pub fn grid_size(&self, n: usize) -> usize {
+        self.grid_size_for(n, 2)
+    }
+
+    #[cfg_attr(doc, katexit::katexit)]
+    /// Number of grid points to evaluate the products of `degree` fields for the `n`-point model
+    ///
+    /// The product of $p$ fields has the modes up to $pN/2$,
+    /// and [Dealiasing::Padding] needs $(p + 1)N/2$ points not to alias them into $|k| < N/2$.
+    pub fn grid_size_for(&self, n: usize, degree: usize) -> usize {
+        match self {
+            // rounded up to even
+            Dealiasing::Padding => ((degree + 1) * n).div_ceil(4) * 2,
+            _ => n,
+        }
+    }
+
+    /// Number of the leading coefficients of the `n`-point model used in the products
+    ///
+    /// [Dealiasing::Padding] drops the Nyquist mode of the original grid,
+    /// since it is not a Nyquist mode on the padded grid.
+    pub fn input_size(&self, n: usize) -> usize {
+        match self {
+            Dealiasing::Padding => n.div_ceil(2),
+            _ => n / 2 + 1,
+        }
+    }
+
+    /// Factors multiplied to the `n / 2 + 1` coefficients of the product for the `n`-point model
+    pub fn filter(&self, n: usize) -> Array1<f64> {
+        let nf = n / 2 + 1;
+        Array::from_shape_fn(nf, |i| match *self {
+            Dealiasing::None => 1.0,
+            Dealiasing::TwoThirds => {
+                if 3 * i < n {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            // Nyquist mode of the original grid is not a Nyquist mode on the padded grid
+            Dealiasing::Padding => {
+                if 2 * i == n {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+            Dealiasing::Exponential { alpha, order } => {
+                let k = 2.0 * i as f64 / n as f64;
+                (-alpha * k.powi(order)).exp()
+            }
+        })
+    }
+}
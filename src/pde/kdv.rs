@@ -0,0 +1,159 @@
+use fftw::types::c64;
+use ndarray::*;
+
+use super::{rfft_wavenumbers, Dealiasing, Pair};
+use crate::traits::*;
+
+#[cfg_attr(doc, katexit::katexit)]
+/// One-dimensional Korteweg-de Vries equation with spectral method
+///
+/// $$
+/// \frac{\partial u}{\partial t} + 6 u \frac{\partial u}{\partial x} + \frac{\partial^3 u}{\partial x^3} = 0
+/// $$
+///
+/// where $u = u(x, t)$ is real value field defined on $x \in [0, L]$
+/// with cyclic boundary condition $u(x, t) = u(x + L, t)$.
+/// The soliton $u = \frac{c}{2} \mathrm{sech}^2 \left(\frac{\sqrt{c}}{2}(x - ct)\right)$ travels with the speed $c$.
+///
+/// The mass $\langle u \rangle$, momentum $\frac{1}{2}\langle u^2 \rangle$
+/// and energy $\langle \frac{1}{2} u_x^2 - u^3 \rangle$ averaged over the system are conserved,
+/// and computed from the Fourier coefficients by [KdV::mass], [KdV::momentum] and [KdV::energy].
+///
+/// Links
+/// -----
+/// - ["Interaction of \"Solitons\" in a Collisionless Plasma and the Recurrence of Initial States", N. J. Zabusky and M. D. Kruskal, PRL 15, 240 (1965)](https://journals.aps.org/prl/abstract/10.1103/PhysRevLett.15.240)
+pub struct KdV {
+    n: usize,
+    length: f64,
+    k: Array1<f64>,
+    dealiasing: Dealiasing,
+    filter: Array1<f64>,
+    u: Pair,
+    // zero-padded buffer for the cubic term of the energy
+    u3: Pair,
+}
+
+impl Clone for KdV {
+    fn clone(&self) -> Self {
+        Self::with_dealiasing(self.n, self.length, self.dealiasing)
+    }
+}
+
+impl ModelSpec for KdV {
+    type Scalar = c64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        self.k.len()
+    }
+}
+
+impl KdV {
+    /// - `n`: Number of grid points
+    /// - `length`: System size $L$
+    pub fn new(n: usize, length: f64) -> Self {
+        Self::with_dealiasing(n, length, Dealiasing::None)
+    }
+
+    /// Create with dealiasing of the nonlinear term
+    pub fn with_dealiasing(n: usize, length: f64, dealiasing: Dealiasing) -> Self {
+        KdV {
+            n,
+            length,
+            k: rfft_wavenumbers(n, length),
+            dealiasing,
+            filter: dealiasing.filter(n),
+            u: Pair::new(dealiasing.grid_size(n)),
+            u3: Pair::new(Dealiasing::Padding.grid_size_for(n, 3)),
+        }
+    }
+
+    /// Mass $\langle u \rangle$
+    pub fn mass<S>(&self, uf: &ArrayBase<S, Ix1>) -> f64
+    where
+        S: Data<Elem = c64>,
+    {
+        uf[0].re
+    }
+
+    /// Momentum $\frac{1}{2}\langle u^2 \rangle$
+    pub fn momentum<S>(&self, uf: &ArrayBase<S, Ix1>) -> f64
+    where
+        S: Data<Elem = c64>,
+    {
+        0.5 * self.spectral_sum(uf, |_| 1.0)
+    }
+
+    /// Energy $\langle \frac{1}{2} u_x^2 - u^3 \rangle$
+    ///
+    /// $u^3$ is evaluated in the real space on the $2N$-point grid with zero-padding
+    /// as [Dealiasing::Padding] regardless of the dealiasing of the model,
+    /// so that the energy is free from aliasing and does not depend on the dealiasing.
+    pub fn energy<S>(&mut self, uf: &ArrayBase<S, Ix1>) -> f64
+    where
+        S: Data<Elem = c64>,
+    {
+        let kinetic = 0.5 * self.spectral_sum(uf, |k| k * k);
+        let ni = Dealiasing::Padding.input_size(self.n);
+        self.u3.c.fill(c64::new(0.0, 0.0));
+        self.u3
+            .coeff_view_mut()
+            .slice_mut(s![..ni])
+            .assign(&uf.slice(s![..ni]));
+        self.u3.c2r();
+        let cubic = self.u3.r.iter().map(|u| u * u * u).sum::<f64>() / self.u3.r.len() as f64;
+        kinetic - cubic
+    }
+
+    /// $\sum_k w(k) |u_k|^2$ over the full wavenumbers
+    fn spectral_sum<S, W>(&self, uf: &ArrayBase<S, Ix1>, w: W) -> f64
+    where
+        S: Data<Elem = c64>,
+        W: Fn(f64) -> f64,
+    {
+        let n = self.n;
+        Zip::indexed(uf).and(&self.k).fold(0.0, |sum, i, u, &k| {
+            // modes except 0 and Nyquist are doubled by the conjugate symmetry
+            let weight = if i == 0 || 2 * i == n { 1.0 } else { 2.0 };
+            sum + weight * w(k) * u.norm_sqr()
+        })
+    }
+}
+
+impl SemiImplicit for KdV {
+    fn nlin<'a, S>(
+        &mut self,
+        uf: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let nf = self.k.len();
+        let ni = self.dealiasing.input_size(self.n);
+        // zero-padding for Dealiasing::Padding
+        self.u.c.fill(c64::new(0.0, 0.0));
+        self.u
+            .coeff_view_mut()
+            .slice_mut(s![..ni])
+            .assign(&uf.slice(s![..ni]));
+        self.u.c2r();
+        for u in self.u.r.iter_mut() {
+            *u = *u * *u;
+        }
+        self.u.r2c();
+        // 6 u u_x = 3 (u^2)_x
+        azip!((
+            uf in &mut *uf,
+            &u2 in self.u.coeff_view().slice(s![..nf]),
+            &k in &self.k,
+            &f in &self.filter
+        ) {
+            *uf = c64::new(0.0, -3.0 * k * f) * u2;
+        });
+        uf
+    }
+
+    fn diag(&self) -> Array1<c64> {
+        // -(ik)^3 = ik^3
+        self.k.mapv(|k| c64::new(0.0, k * k * k))
+    }
+}
@@ -2,7 +2,7 @@ use fftw::types::c64;
 use ndarray::*;
 use std::f64::consts::PI;
 
-use super::Pair;
+use super::{Dealiasing, Pair};
 use crate::traits::*;
 
 #[cfg_attr(doc, katexit::katexit)]
@@ -25,13 +25,15 @@ pub struct KSE {
     nf: usize,
     length: f64,
     k: Array1<c64>,
+    dealiasing: Dealiasing,
+    filter: Array1<f64>,
     u: Pair,
     ux: Pair,
 }
 
 impl Clone for KSE {
     fn clone(&self) -> Self {
-        Self::new(self.n, self.length)
+        Self::with_dealiasing(self.n, self.length, self.dealiasing)
     }
 }
 
@@ -45,15 +47,23 @@ impl ModelSpec for KSE {
 
 impl KSE {
     pub fn new(n: usize, length: f64) -> Self {
+        Self::with_dealiasing(n, length, Dealiasing::None)
+    }
+
+    /// Create with dealiasing of the nonlinear term
+    pub fn with_dealiasing(n: usize, length: f64, dealiasing: Dealiasing) -> Self {
         let nf = n / 2 + 1;
         let k0 = 2.0 * PI / length;
+        let m = dealiasing.grid_size(n);
         KSE {
             n,
             nf,
             length,
             k: Array::from_iter((0..nf).map(|i| c64::new(0.0, k0 * i as f64))),
-            u: Pair::new(n),
-            ux: Pair::new(n),
+            dealiasing,
+            filter: dealiasing.filter(n),
+            u: Pair::new(m),
+            ux: Pair::new(m),
         }
     }
 }
@@ -66,7 +76,17 @@ impl SemiImplicit for KSE {
     where
         S: DataMut<Elem = Self::Scalar>,
     {
-        azip!((u in &mut self.u.coeff_view_mut(), ux in &mut self.ux.coeff_view_mut(), &k in &self.k, &uf in &*uf) {
+        let nf = self.nf;
+        let ni = self.dealiasing.input_size(self.n);
+        // zero-padding for Dealiasing::Padding
+        self.u.c.fill(c64::new(0.0, 0.0));
+        self.ux.c.fill(c64::new(0.0, 0.0));
+        azip!((
+            u in self.u.coeff_view_mut().slice_mut(s![..ni]),
+            ux in self.ux.coeff_view_mut().slice_mut(s![..ni]),
+            &k in self.k.slice(s![..ni]),
+            &uf in uf.slice(s![..ni])
+        ) {
             *u = uf;
             *ux = k * uf;
         });
@@ -76,7 +96,9 @@ impl SemiImplicit for KSE {
             *u = -*u * ux;
         });
         self.u.r2c();
-        uf.as_slice_mut().unwrap().copy_from_slice(&self.u.c);
+        azip!((uf in &mut *uf, &u in self.u.coeff_view().slice(s![..nf]), &f in &self.filter) {
+            *uf = u * f;
+        });
         uf
     }
 
@@ -1,15 +1,28 @@
 //! Example nonlinear PDEs with spectral (Fourier-Galerkin) method
 
+mod cgle;
+mod dealias;
+mod kdv;
 mod kse;
+mod navier_stokes;
+mod nls;
 mod she;
 
+pub use self::cgle::CGLE;
+pub use self::dealias::Dealiasing;
+pub use self::kdv::KdV;
 pub use self::kse::KSE;
+pub use self::navier_stokes::NavierStokes2D;
+pub use self::nls::NLS;
 pub use self::she::SHE;
 
 use fftw::array::*;
 use fftw::plan::*;
 use fftw::types::*;
 use ndarray::*;
+use std::f64::consts::PI;
+
+use crate::error::Result;
 
 /// Pair of one-dimensional Real/Complex aligned arrays
 pub struct Pair {
@@ -21,12 +34,17 @@ pub struct Pair {
 
 impl Pair {
     pub fn new(n: usize) -> Self {
+        Self::try_new(n).expect("Failed to create FFTW plans")
+    }
+
+    /// Fallible version of [Pair::new], which fails if FFTW cannot create plans
+    pub fn try_new(n: usize) -> Result<Self> {
         let nf = n / 2 + 1;
         let mut r = AlignedVec::new(n);
         let mut c = AlignedVec::new(nf);
-        let r2c = R2CPlan::new(&[n], &mut r, &mut c, Flag::MEASURE).unwrap();
-        let c2r = C2RPlan::new(&[n], &mut c, &mut r, Flag::MEASURE).unwrap();
-        Pair { r, c, r2c, c2r }
+        let r2c = R2CPlan::new(&[n], &mut r, &mut c, Flag::MEASURE)?;
+        let c2r = C2RPlan::new(&[n], &mut c, &mut r, Flag::MEASURE)?;
+        Ok(Pair { r, c, r2c, c2r })
     }
 
     pub fn r2c(&mut self) {
@@ -52,19 +70,19 @@ impl Pair {
         &self.c
     }
 
-    pub fn real_view(&self) -> ArrayView1<f64> {
+    pub fn real_view(&self) -> ArrayView1<'_, f64> {
         ArrayView::from_shape(self.r.len(), &self.r).unwrap()
     }
 
-    pub fn coeff_view(&self) -> ArrayView1<c64> {
+    pub fn coeff_view(&self) -> ArrayView1<'_, c64> {
         ArrayView::from_shape(self.c.len(), &self.c).unwrap()
     }
 
-    pub fn real_view_mut(&mut self) -> ArrayViewMut1<f64> {
+    pub fn real_view_mut(&mut self) -> ArrayViewMut1<'_, f64> {
         ArrayViewMut::from_shape(self.r.len(), &mut self.r).unwrap()
     }
 
-    pub fn coeff_view_mut(&mut self) -> ArrayViewMut1<c64> {
+    pub fn coeff_view_mut(&mut self) -> ArrayViewMut1<'_, c64> {
         ArrayViewMut::from_shape(self.c.len(), &mut self.c).unwrap()
     }
 }
@@ -74,3 +92,252 @@ impl Clone for Pair {
         Pair::new(self.r.len())
     }
 }
+
+/// Pair of N-dimensional Real/Complex aligned arrays
+///
+/// The real array has `shape` in the standard (row-major) layout,
+/// and the complex array has the same shape except for the last axis of length `n / 2 + 1`
+/// since the coefficients satisfy $c_{-k} = c_k^*$ as in FFTW.
+/// The normalization is same as [Pair], i.e. [PairND::r2c] divides the coefficients by the number of points.
+#[cfg_attr(doc, katexit::katexit)]
+pub struct PairND<D: Dimension> {
+    pub r: AlignedVec<f64>,
+    pub c: AlignedVec<c64>,
+    shape: D,
+    coeff_shape: D,
+    r2c: R2CPlan64,
+    c2r: C2RPlan64,
+}
+
+/// Pair of two-dimensional Real/Complex aligned arrays
+pub type Pair2D = PairND<Ix2>;
+
+impl<D: Dimension> PairND<D> {
+    pub fn new<Sh: IntoDimension<Dim = D>>(shape: Sh) -> Self {
+        Self::try_new(shape).expect("Failed to create FFTW plans")
+    }
+
+    /// Fallible version of [PairND::new], which fails if FFTW cannot create plans
+    pub fn try_new<Sh: IntoDimension<Dim = D>>(shape: Sh) -> Result<Self> {
+        let shape = shape.into_dimension();
+        let mut coeff_shape = shape.clone();
+        let last = shape.ndim() - 1;
+        coeff_shape[last] = shape[last] / 2 + 1;
+        let mut r = AlignedVec::new(shape.size());
+        let mut c = AlignedVec::new(coeff_shape.size());
+        let r2c = R2CPlan::new(shape.slice(), &mut r, &mut c, Flag::MEASURE)?;
+        let c2r = C2RPlan::new(shape.slice(), &mut c, &mut r, Flag::MEASURE)?;
+        Ok(PairND {
+            r,
+            c,
+            shape,
+            coeff_shape,
+            r2c,
+            c2r,
+        })
+    }
+
+    /// Shape of the real array
+    pub fn shape(&self) -> D {
+        self.shape.clone()
+    }
+
+    /// Shape of the complex array
+    pub fn coeff_shape(&self) -> D {
+        self.coeff_shape.clone()
+    }
+
+    pub fn r2c(&mut self) {
+        self.r2c.r2c(&mut self.r, &mut self.c).unwrap();
+        let n = 1.0 / self.r.len() as f64;
+        for v in self.c.iter_mut() {
+            *v *= n;
+        }
+    }
+
+    /// Inverse transform. Note that the complex array is overwritten by FFTW for the multi-dimensional case.
+    pub fn c2r(&mut self) {
+        self.c2r.c2r(&mut self.c, &mut self.r).unwrap();
+    }
+
+    pub fn to_r<'a>(&'a mut self, c: &[c64]) -> ArrayView<'a, f64, D> {
+        self.c.copy_from_slice(c);
+        self.c2r();
+        self.real_view()
+    }
+
+    pub fn to_c<'a>(&'a mut self, r: &[f64]) -> ArrayView<'a, c64, D> {
+        self.r.copy_from_slice(r);
+        self.r2c();
+        self.coeff_view()
+    }
+
+    pub fn real_view(&self) -> ArrayView<'_, f64, D> {
+        ArrayView::from_shape(self.shape.clone(), &self.r).unwrap()
+    }
+
+    pub fn coeff_view(&self) -> ArrayView<'_, c64, D> {
+        ArrayView::from_shape(self.coeff_shape.clone(), &self.c).unwrap()
+    }
+
+    pub fn real_view_mut(&mut self) -> ArrayViewMut<'_, f64, D> {
+        ArrayViewMut::from_shape(self.shape.clone(), &mut self.r).unwrap()
+    }
+
+    pub fn coeff_view_mut(&mut self) -> ArrayViewMut<'_, c64, D> {
+        ArrayViewMut::from_shape(self.coeff_shape.clone(), &mut self.c).unwrap()
+    }
+}
+
+impl<D: Dimension> Clone for PairND<D> {
+    fn clone(&self) -> Self {
+        PairND::new(self.shape.clone())
+    }
+}
+
+/// Pair of N-dimensional complex aligned arrays for a complex field and its Fourier coefficients
+///
+/// Both arrays have `shape` in the standard (row-major) layout,
+/// and the coefficients are stored in the order of FFT as in [wavenumbers].
+/// [CPairND::forward] divides the coefficients by the number of points as [PairND::r2c].
+pub struct CPairND<D: Dimension> {
+    pub x: AlignedVec<c64>,
+    pub c: AlignedVec<c64>,
+    shape: D,
+    forward: C2CPlan64,
+    backward: C2CPlan64,
+}
+
+/// Pair of one-dimensional complex aligned arrays
+pub type CPair = CPairND<Ix1>;
+/// Pair of two-dimensional complex aligned arrays
+pub type CPair2D = CPairND<Ix2>;
+
+impl<D: Dimension> CPairND<D> {
+    pub fn new<Sh: IntoDimension<Dim = D>>(shape: Sh) -> Self {
+        Self::try_new(shape).expect("Failed to create FFTW plans")
+    }
+
+    /// Fallible version of [CPairND::new], which fails if FFTW cannot create plans
+    pub fn try_new<Sh: IntoDimension<Dim = D>>(shape: Sh) -> Result<Self> {
+        let shape = shape.into_dimension();
+        let mut x = AlignedVec::new(shape.size());
+        let mut c = AlignedVec::new(shape.size());
+        let forward = C2CPlan::new(shape.slice(), &mut x, &mut c, Sign::Forward, Flag::MEASURE)?;
+        let backward = C2CPlan::new(shape.slice(), &mut c, &mut x, Sign::Backward, Flag::MEASURE)?;
+        Ok(CPairND {
+            x,
+            c,
+            shape,
+            forward,
+            backward,
+        })
+    }
+
+    /// Shape of both arrays
+    pub fn shape(&self) -> D {
+        self.shape.clone()
+    }
+
+    /// Transform the field into the coefficients
+    pub fn forward(&mut self) {
+        self.forward.c2c(&mut self.x, &mut self.c).unwrap();
+        let n = 1.0 / self.x.len() as f64;
+        for v in self.c.iter_mut() {
+            *v *= n;
+        }
+    }
+
+    /// Transform the coefficients into the field
+    pub fn backward(&mut self) {
+        self.backward.c2c(&mut self.c, &mut self.x).unwrap();
+    }
+
+    pub fn to_field<'a>(&'a mut self, c: &[c64]) -> ArrayView<'a, c64, D> {
+        self.c.copy_from_slice(c);
+        self.backward();
+        self.field_view()
+    }
+
+    pub fn to_c<'a>(&'a mut self, x: &[c64]) -> ArrayView<'a, c64, D> {
+        self.x.copy_from_slice(x);
+        self.forward();
+        self.coeff_view()
+    }
+
+    pub fn field_view(&self) -> ArrayView<'_, c64, D> {
+        ArrayView::from_shape(self.shape.clone(), &self.x).unwrap()
+    }
+
+    pub fn coeff_view(&self) -> ArrayView<'_, c64, D> {
+        ArrayView::from_shape(self.shape.clone(), &self.c).unwrap()
+    }
+
+    pub fn field_view_mut(&mut self) -> ArrayViewMut<'_, c64, D> {
+        ArrayViewMut::from_shape(self.shape.clone(), &mut self.x).unwrap()
+    }
+
+    pub fn coeff_view_mut(&mut self) -> ArrayViewMut<'_, c64, D> {
+        ArrayViewMut::from_shape(self.shape.clone(), &mut self.c).unwrap()
+    }
+}
+
+impl<D: Dimension> Clone for CPairND<D> {
+    fn clone(&self) -> Self {
+        CPairND::new(self.shape.clone())
+    }
+}
+
+/// Wavenumbers of the complex-to-complex transform of length `n`
+/// in the order of FFT, i.e. $2\pi/L \times [0, 1, \ldots, \lceil n/2 \rceil - 1, -\lfloor n/2 \rfloor, \ldots, -1]$
+#[cfg_attr(doc, katexit::katexit)]
+pub fn wavenumbers(n: usize, length: f64) -> Array1<f64> {
+    let k0 = 2.0 * PI / length;
+    Array::from_iter((0..n).map(|i| {
+        if 2 * i < n {
+            k0 * i as f64
+        } else {
+            k0 * (i as f64 - n as f64)
+        }
+    }))
+}
+
+/// Wavenumbers of the real-to-complex transform of length `n`, i.e. $2\pi/L \times [0, 1, \ldots, n/2]$
+#[cfg_attr(doc, katexit::katexit)]
+pub fn rfft_wavenumbers(n: usize, length: f64) -> Array1<f64> {
+    let k0 = 2.0 * PI / length;
+    Array::from_iter((0..n / 2 + 1).map(|i| k0 * i as f64))
+}
+
+/// Wavenumbers on the coefficients of [Pair2D] for the box $[0, L_x] \times [0, L_y]$
+///
+/// The real field $u(x_i, y_j)$ is stored in `r[(i, j)]`,
+/// and thus $k_y$ lies on the last axis truncated by the real-to-complex transform.
+#[cfg_attr(doc, katexit::katexit)]
+#[derive(Debug, Clone)]
+pub struct Wavenumbers2D {
+    /// $k_x$ for each coefficient
+    pub kx: Array2<f64>,
+    /// $k_y$ for each coefficient
+    pub ky: Array2<f64>,
+    /// $|k|^2 = k_x^2 + k_y^2$ for each coefficient
+    pub k2: Array2<f64>,
+}
+
+impl Wavenumbers2D {
+    /// `shape` is the shape of the real array, i.e. the number of grid points for each axis
+    pub fn new(shape: (usize, usize), length: (f64, f64)) -> Self {
+        let kx = wavenumbers(shape.0, length.0);
+        let ky = rfft_wavenumbers(shape.1, length.1);
+        let dim = (kx.len(), ky.len());
+        let kx = Array::from_shape_fn(dim, |(i, _)| kx[i]);
+        let ky = Array::from_shape_fn(dim, |(_, j)| ky[j]);
+        let k2 = &kx * &kx + &ky * &ky;
+        Wavenumbers2D { kx, ky, k2 }
+    }
+
+    /// Shape of the coefficients
+    pub fn dim(&self) -> (usize, usize) {
+        self.kx.dim()
+    }
+}
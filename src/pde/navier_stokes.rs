@@ -0,0 +1,202 @@
+use fftw::types::c64;
+use ndarray::*;
+use std::f64::consts::PI;
+
+use super::{Pair2D, Wavenumbers2D};
+use crate::traits::*;
+
+#[cfg_attr(doc, katexit::katexit)]
+/// Two-dimensional incompressible Navier-Stokes equation in vorticity form with spectral method
+///
+/// $$
+/// \frac{\partial \omega}{\partial t} + J(\psi, \omega) =
+/// \nu \nabla^2 \omega - \nu_h (-\nabla^2)^p \omega - \mu \omega + f
+/// $$
+///
+/// where $\omega = \omega(x, y, t)$ is the vorticity on the doubly periodic box $[0, L_x] \times [0, L_y]$,
+/// and $\psi$ is the streamfunction determined by $\nabla^2 \psi = -\omega$.
+/// The velocity is $(u, v) = (\partial_y \psi, -\partial_x \psi)$,
+/// and $J(\psi, \omega) = u \partial_x \omega + v \partial_y \omega$ is the advection term.
+/// The hyperviscosity $\nu_h$, linear drag $\mu$ and forcing $f$ are disabled by default.
+/// The Kolmogorov forcing is the vorticity of the body force $F \sin(k_f y) \hat{x}$,
+/// i.e. $f = -F k_f \cos(k_f y)$.
+///
+/// The state is the Fourier coefficients of $\omega$ in the layout of [Pair2D],
+/// and the linear terms are treated as the stiff diagonal part.
+/// Parameters must be set before creating a scheme,
+/// since [SemiImplicit::diag] is evaluated when a scheme is created.
+#[derive(Clone)]
+pub struct NavierStokes2D {
+    n: (usize, usize),
+    length: (f64, f64),
+    nu: f64,
+    nu_h: f64,
+    p: i32,
+    mu: f64,
+    k: Wavenumbers2D,
+    inv_k2: Array2<f64>,
+    forcing: Array2<c64>,
+    w: Pair2D,
+    wx: Pair2D,
+    wy: Pair2D,
+    u: Pair2D,
+    v: Pair2D,
+}
+
+impl ModelSpec for NavierStokes2D {
+    type Scalar = c64;
+    type Dim = Ix2;
+    fn model_size(&self) -> (usize, usize) {
+        self.k.dim()
+    }
+}
+
+impl NavierStokes2D {
+    /// - `n`: Number of grid points $(N_x, N_y)$
+    /// - `length`: System size $(L_x, L_y)$
+    /// - `nu`: Kinematic viscosity $\nu$
+    pub fn new(n: (usize, usize), length: (f64, f64), nu: f64) -> Self {
+        let k = Wavenumbers2D::new(n, length);
+        let inv_k2 = k.k2.mapv(|k2| if k2 > 0.0 { 1.0 / k2 } else { 0.0 });
+        NavierStokes2D {
+            n,
+            length,
+            nu,
+            nu_h: 0.0,
+            p: 2,
+            mu: 0.0,
+            forcing: Array::zeros(k.dim()),
+            inv_k2,
+            k,
+            w: Pair2D::new(n),
+            wx: Pair2D::new(n),
+            wy: Pair2D::new(n),
+            u: Pair2D::new(n),
+            v: Pair2D::new(n),
+        }
+    }
+
+    /// Set the hyperviscosity $\nu_h (-\nabla^2)^p$
+    pub fn set_hyperviscosity(&mut self, nu_h: f64, p: i32) {
+        self.nu_h = nu_h;
+        self.p = p;
+    }
+
+    /// Set the coefficient of linear drag $\mu$
+    pub fn set_drag(&mut self, mu: f64) {
+        self.mu = mu;
+    }
+
+    /// Set the Kolmogorov forcing with amplitude $F$ and wavenumber $k_f = 2\pi n_f / L_y$
+    pub fn set_kolmogorov_forcing(&mut self, amplitude: f64, nf: usize) {
+        assert!(
+            nf > 0 && nf < self.n.1 / 2,
+            "Forcing wavenumber is out of range"
+        );
+        let kf = 2.0 * PI * nf as f64 / self.length.1;
+        self.forcing.fill(c64::new(0.0, 0.0));
+        // cos(kf y) = (e^{i kf y} + e^{-i kf y}) / 2
+        self.forcing[(0, nf)] = c64::new(-0.5 * amplitude * kf, 0.0);
+    }
+
+    pub fn wavenumbers(&self) -> &Wavenumbers2D {
+        &self.k
+    }
+
+    /// Streamfunction $\psi$ for the vorticity $\omega$ in Fourier space
+    pub fn streamfunction<S>(&self, w: &ArrayBase<S, Ix2>) -> Array2<c64>
+    where
+        S: Data<Elem = c64>,
+    {
+        w * &self.inv_k2.mapv(|k| c64::new(k, 0.0))
+    }
+
+    /// Kinetic energy $\frac{1}{2} \langle u^2 + v^2 \rangle$ averaged over the box
+    pub fn energy<S>(&self, w: &ArrayBase<S, Ix2>) -> f64
+    where
+        S: Data<Elem = c64>,
+    {
+        self.spectral_mean(w, &self.inv_k2)
+    }
+
+    /// Enstrophy $\frac{1}{2} \langle \omega^2 \rangle$ averaged over the box
+    pub fn enstrophy<S>(&self, w: &ArrayBase<S, Ix2>) -> f64
+    where
+        S: Data<Elem = c64>,
+    {
+        self.spectral_mean(w, &Array::ones(self.k.dim()))
+    }
+
+    /// $\frac{1}{2} \sum_k c_k |\omega_k|^2$ over the full wavenumber plane
+    fn spectral_mean<S>(&self, w: &ArrayBase<S, Ix2>, c: &Array2<f64>) -> f64
+    where
+        S: Data<Elem = c64>,
+    {
+        let ny = self.n.1;
+        let mut sum = 0.0;
+        Zip::indexed(w).and(c).for_each(|(_, j), w, c| {
+            // modes except j = 0 and Nyquist are doubled by the conjugate symmetry
+            let weight = if j == 0 || 2 * j == ny { 1.0 } else { 2.0 };
+            sum += weight * c * w.norm_sqr();
+        });
+        0.5 * sum
+    }
+}
+
+impl SemiImplicit for NavierStokes2D {
+    fn nlin<'a, S>(
+        &mut self,
+        wf: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let i = c64::new(0.0, 1.0);
+        azip!((
+            wx in &mut self.wx.coeff_view_mut(),
+            wy in &mut self.wy.coeff_view_mut(),
+            &kx in &self.k.kx,
+            &ky in &self.k.ky,
+            &w in &*wf
+        ) {
+            *wx = i * kx * w;
+            *wy = i * ky * w;
+        });
+        azip!((
+            u in &mut self.u.coeff_view_mut(),
+            v in &mut self.v.coeff_view_mut(),
+            &kx in &self.k.kx,
+            &ky in &self.k.ky,
+            &inv_k2 in &self.inv_k2,
+            &w in &*wf
+        ) {
+            *u = i * ky * inv_k2 * w;
+            *v = -i * kx * inv_k2 * w;
+        });
+        self.wx.c2r();
+        self.wy.c2r();
+        self.u.c2r();
+        self.v.c2r();
+        azip!((
+            j in &mut self.w.real_view_mut(),
+            &wx in &self.wx.real_view(),
+            &wy in &self.wy.real_view(),
+            &u in &self.u.real_view(),
+            &v in &self.v.real_view()
+        ) {
+            *j = -(u * wx + v * wy);
+        });
+        self.w.r2c();
+        azip!((w in &mut *wf, &j in &self.w.coeff_view(), &f in &self.forcing) {
+            *w = j + f;
+        });
+        wf
+    }
+
+    fn diag(&self) -> Array2<c64> {
+        let (nu, nu_h, p, mu) = (self.nu, self.nu_h, self.p, self.mu);
+        self.k
+            .k2
+            .mapv(|k2| c64::new(-nu * k2 - nu_h * k2.powi(p) - mu, 0.0))
+    }
+}
@@ -0,0 +1,125 @@
+use fftw::types::c64;
+use ndarray::*;
+
+use super::{wavenumbers, CPair};
+use crate::traits::*;
+
+#[cfg_attr(doc, katexit::katexit)]
+/// One-dimensional nonlinear Schrödinger equation with spectral method
+///
+/// $$
+/// i \frac{\partial \psi}{\partial t} + \frac{1}{2} \frac{\partial^2 \psi}{\partial x^2} + \kappa |\psi|^2 \psi = 0
+/// $$
+///
+/// where $\psi = \psi(x, t)$ is complex value field defined on $x \in [0, L]$
+/// with cyclic boundary condition $\psi(x, t) = \psi(x + L, t)$.
+/// The nonlinearity is focusing for $\kappa > 0$ and defocusing for $\kappa < 0$.
+/// The focusing case has the bright soliton $\psi = \eta\, \mathrm{sech}(\eta x) e^{i \eta^2 t / 2}$ for $\kappa = 1$.
+/// The state is the Fourier coefficients of $\psi$ in the layout of [CPair](super::CPair).
+///
+/// The mass $\langle |\psi|^2 \rangle$, momentum $\langle \mathrm{Im}(\psi^* \psi_x) \rangle$
+/// and energy $\langle \frac{1}{2} |\psi_x|^2 - \frac{\kappa}{2} |\psi|^4 \rangle$ averaged over the system are conserved,
+/// and computed from the Fourier coefficients by [NLS::mass], [NLS::momentum] and [NLS::energy].
+#[derive(Clone)]
+pub struct NLS {
+    kappa: f64,
+    k: Array1<f64>,
+    psi: CPair,
+}
+
+impl ModelSpec for NLS {
+    type Scalar = c64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        self.k.len()
+    }
+}
+
+impl NLS {
+    /// - `n`: Number of grid points
+    /// - `length`: System size $L$
+    /// - `kappa`: Strength of the nonlinearity $\kappa$
+    pub fn new(n: usize, length: f64, kappa: f64) -> Self {
+        NLS {
+            kappa,
+            k: wavenumbers(n, length),
+            psi: CPair::new(n),
+        }
+    }
+
+    /// Focusing equation, i.e. $\kappa = 1$
+    pub fn focusing(n: usize, length: f64) -> Self {
+        Self::new(n, length, 1.0)
+    }
+
+    /// Defocusing equation, i.e. $\kappa = -1$
+    pub fn defocusing(n: usize, length: f64) -> Self {
+        Self::new(n, length, -1.0)
+    }
+
+    /// Mass $\langle |\psi|^2 \rangle$
+    pub fn mass<S>(&self, psi: &ArrayBase<S, Ix1>) -> f64
+    where
+        S: Data<Elem = c64>,
+    {
+        self.spectral_sum(psi, |_| 1.0)
+    }
+
+    /// Momentum $\langle \mathrm{Im}(\psi^* \psi_x) \rangle$
+    pub fn momentum<S>(&self, psi: &ArrayBase<S, Ix1>) -> f64
+    where
+        S: Data<Elem = c64>,
+    {
+        self.spectral_sum(psi, |k| k)
+    }
+
+    /// Energy $\langle \frac{1}{2} |\psi_x|^2 - \frac{\kappa}{2} |\psi|^4 \rangle$
+    ///
+    /// This uses the FFT buffer of the model to evaluate $|\psi|^4$ in the real space.
+    pub fn energy<S>(&mut self, psi: &ArrayBase<S, Ix1>) -> f64
+    where
+        S: Data<Elem = c64>,
+    {
+        let kinetic = 0.5 * self.spectral_sum(psi, |k| k * k);
+        self.psi.coeff_view_mut().assign(psi);
+        self.psi.backward();
+        let quartic =
+            self.psi.x.iter().map(|p| p.norm_sqr().powi(2)).sum::<f64>() / self.psi.x.len() as f64;
+        kinetic - 0.5 * self.kappa * quartic
+    }
+
+    /// $\sum_k w(k) |\psi_k|^2$
+    fn spectral_sum<S, W>(&self, psi: &ArrayBase<S, Ix1>, w: W) -> f64
+    where
+        S: Data<Elem = c64>,
+        W: Fn(f64) -> f64,
+    {
+        Zip::from(psi)
+            .and(&self.k)
+            .fold(0.0, |sum, p, &k| sum + w(k) * p.norm_sqr())
+    }
+}
+
+impl SemiImplicit for NLS {
+    fn nlin<'a, S>(
+        &mut self,
+        psi: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let c = c64::new(0.0, self.kappa);
+        self.psi.coeff_view_mut().assign(psi);
+        self.psi.backward();
+        for p in self.psi.x.iter_mut() {
+            *p *= c * p.norm_sqr();
+        }
+        self.psi.forward();
+        psi.assign(&self.psi.coeff_view());
+        psi
+    }
+
+    fn diag(&self) -> Array1<c64> {
+        self.k.mapv(|k| c64::new(0.0, -0.5 * k * k))
+    }
+}
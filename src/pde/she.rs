@@ -2,7 +2,7 @@ use fftw::types::c64;
 use ndarray::*;
 use std::f64::consts::PI;
 
-use super::Pair;
+use super::{Dealiasing, Pair};
 use crate::traits::*;
 
 #[cfg_attr(doc, katexit::katexit)]
@@ -24,6 +24,7 @@ use crate::traits::*;
 ///
 #[derive(Clone)]
 pub struct SHE {
+    n: usize,
     nf: usize,
 
     /// Parameter for linear stablity
@@ -32,6 +33,8 @@ pub struct SHE {
     qc: f64,
 
     k: Array1<c64>,
+    dealiasing: Dealiasing,
+    filter: Array1<f64>,
     u: Pair,
 }
 
@@ -49,15 +52,24 @@ impl SHE {
     /// - `r`: Stability parameter $r$
     /// - `lc`: Length scale of instablity, i.e. $q_c = 2\pi / l_c$
     pub fn new(n: usize, length: f64, r: f64, lc: f64) -> Self {
+        Self::with_dealiasing(n, length, r, lc, Dealiasing::None)
+    }
+
+    /// Create with dealiasing of the nonlinear term. See [SHE::new] for other arguments.
+    pub fn with_dealiasing(n: usize, length: f64, r: f64, lc: f64, dealiasing: Dealiasing) -> Self {
         let nf = n / 2 + 1;
         let k0 = 2.0 * PI / length;
         let qc = 2.0 * PI / lc;
         SHE {
+            n,
             nf,
             r,
             qc,
             k: Array::from_iter((0..nf).map(|i| c64::new(0.0, k0 * i as f64))),
-            u: Pair::new(n),
+            dealiasing,
+            filter: dealiasing.filter(n),
+            // u^3 is cubic
+            u: Pair::new(dealiasing.grid_size_for(n, 3)),
         }
     }
 }
@@ -73,7 +85,11 @@ impl SemiImplicit for SHE {
         let c2 = 1.64;
         let c3 = 1.0;
 
-        azip!((u in &mut self.u.coeff_view_mut(), &uf in &*uf) {
+        let nf = self.nf;
+        let ni = self.dealiasing.input_size(self.n);
+        // zero-padding for Dealiasing::Padding
+        self.u.c.fill(c64::new(0.0, 0.0));
+        azip!((u in self.u.coeff_view_mut().slice_mut(s![..ni]), &uf in uf.slice(s![..ni])) {
             *u = uf;
         });
         self.u.c2r();
@@ -81,7 +97,9 @@ impl SemiImplicit for SHE {
             *u = c2 * *u * *u - c3 * *u * *u * *u;
         });
         self.u.r2c();
-        uf.as_slice_mut().unwrap().copy_from_slice(&self.u.c);
+        azip!((uf in &mut *uf, &u in self.u.coeff_view().slice(s![..nf]), &f in &self.filter) {
+            *uf = u * f;
+        });
         uf
     }
 
@@ -3,23 +3,25 @@
 use ndarray::*;
 use ndarray_linalg::*;
 
+use super::complex::imaginary_unit;
 use super::traits::*;
 
 /// Linear ODE with diagonalized matrix (exactly solvable)
 #[derive(Debug, Clone)]
-pub struct Diagonal<F: SemiImplicit> {
+pub struct Diagonal<F: NonAutonomousSemiImplicit> {
     exp_diag: Array<F::Scalar, F::Dim>,
     diag: Array<F::Scalar, F::Dim>,
     dt: <F::Scalar as Scalar>::Real,
 }
 
-impl<F: SemiImplicit> TimeStep for Diagonal<F> {
+impl<F: NonAutonomousSemiImplicit> TimeStep for Diagonal<F> {
     type Time = <F::Scalar as Scalar>::Real;
 
     fn get_dt(&self) -> Self::Time {
         self.dt
     }
     fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
         Zip::from(&mut self.exp_diag)
             .and(&self.diag)
             .for_each(|a, &b| {
@@ -28,7 +30,7 @@ impl<F: SemiImplicit> TimeStep for Diagonal<F> {
     }
 }
 
-impl<F: SemiImplicit> ModelSpec for Diagonal<F> {
+impl<F: NonAutonomousSemiImplicit> ModelSpec for Diagonal<F> {
     type Scalar = F::Scalar;
     type Dim = F::Dim;
 
@@ -37,7 +39,7 @@ impl<F: SemiImplicit> ModelSpec for Diagonal<F> {
     }
 }
 
-impl<F: SemiImplicit> TimeEvolution for Diagonal<F> {
+impl<F: NonAutonomousSemiImplicit> TimeEvolution for Diagonal<F> {
     fn iterate<'a, S>(
         &mut self,
         x: &'a mut ArrayBase<S, Self::Dim>,
@@ -52,9 +54,9 @@ impl<F: SemiImplicit> TimeEvolution for Diagonal<F> {
     }
 }
 
-impl<F: SemiImplicit> Diagonal<F> {
+impl<F: NonAutonomousSemiImplicit> Diagonal<F> {
     fn new(f: F, dt: <Self as TimeStep>::Time) -> Self {
-        let diag = f.diag();
+        let diag = f.lin_diag();
         let mut exp_diag = diag.to_owned();
         for v in exp_diag.iter_mut() {
             *v = v.mul_real(dt).exp();
@@ -64,9 +66,10 @@ impl<F: SemiImplicit> Diagonal<F> {
 }
 
 #[derive(Debug, Clone)]
-pub struct DiagRK4<F: SemiImplicit> {
+pub struct DiagRK4<F: NonAutonomousSemiImplicit> {
     nlin: F,
     lin: Diagonal<F>,
+    t: <Diagonal<F> as TimeStep>::Time,
     dt: <Diagonal<F> as TimeStep>::Time,
     x: Array<F::Scalar, F::Dim>,
     lx: Array<F::Scalar, F::Dim>,
@@ -75,7 +78,7 @@ pub struct DiagRK4<F: SemiImplicit> {
     k3: Array<F::Scalar, F::Dim>,
 }
 
-impl<F: SemiImplicit> Scheme for DiagRK4<F> {
+impl<F: NonAutonomousSemiImplicit> Scheme for DiagRK4<F> {
     type Core = F;
     fn new(nlin: F, dt: Self::Time) -> Self {
         let lin = Diagonal::new(nlin.clone(), dt / F::Scalar::real(2.0));
@@ -87,6 +90,7 @@ impl<F: SemiImplicit> Scheme for DiagRK4<F> {
         DiagRK4 {
             nlin,
             lin,
+            t: F::Scalar::real(0.0),
             dt,
             x,
             lx,
@@ -103,7 +107,7 @@ impl<F: SemiImplicit> Scheme for DiagRK4<F> {
     }
 }
 
-impl<F: SemiImplicit> TimeStep for DiagRK4<F> {
+impl<F: NonAutonomousSemiImplicit> TimeStep for DiagRK4<F> {
     type Time = <Diagonal<F> as TimeStep>::Time;
 
     fn get_dt(&self) -> Self::Time {
@@ -111,20 +115,31 @@ impl<F: SemiImplicit> TimeStep for DiagRK4<F> {
     }
 
     fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
         self.lin.set_dt(dt / F::Scalar::real(2.0));
     }
 }
 
-impl<F: SemiImplicit> ModelSpec for DiagRK4<F> {
+impl<F: NonAutonomousSemiImplicit> CurrentTime for DiagRK4<F> {
+    fn get_time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+}
+
+impl<F: NonAutonomousSemiImplicit> ModelSpec for DiagRK4<F> {
     type Scalar = F::Scalar;
     type Dim = F::Dim;
 
     fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
-        self.nlin.model_size() // TODO check
+        self.nlin.model_size()
     }
 }
 
-impl<F: SemiImplicit> TimeEvolution for DiagRK4<F> {
+impl<F: NonAutonomousSemiImplicit> TimeEvolution for DiagRK4<F> {
     fn iterate<'a, S>(
         &mut self,
         x: &'a mut ArrayBase<S, Self::Dim>,
@@ -144,22 +159,22 @@ impl<F: SemiImplicit> TimeEvolution for DiagRK4<F> {
         self.x.zip_mut_with(x, |buf, x| *buf = *x);
         self.lx.zip_mut_with(x, |buf, lx| *buf = *lx);
         l.iterate(&mut self.lx);
-        let k1 = f.nlin(x);
+        let k1 = f.nlin_t(self.t, x);
         self.k1.zip_mut_with(k1, |buf, k1| *buf = *k1);
         Zip::from(&mut *k1).and(&self.x).for_each(|k1, &x_| {
             *k1 = x_ + k1.mul_real(dt_2);
         });
-        let k2 = f.nlin(l.iterate(k1));
+        let k2 = f.nlin_t(self.t + dt_2, l.iterate(k1));
         self.k2.zip_mut_with(k2, |buf, k| *buf = *k);
         Zip::from(&mut *k2).and(&self.lx).for_each(|k2, &lx| {
             *k2 = lx + k2.mul_real(dt_2);
         });
-        let k3 = f.nlin(k2);
+        let k3 = f.nlin_t(self.t + dt_2, k2);
         self.k3.zip_mut_with(k3, |buf, k| *buf = *k);
         Zip::from(&mut *k3).and(&self.lx).for_each(|k3, &lx| {
             *k3 = lx + k3.mul_real(dt);
         });
-        let k4 = f.nlin(l.iterate(k3));
+        let k4 = f.nlin_t(self.t + dt, l.iterate(k3));
         Zip::from(&mut self.x)
             .and(&self.k1)
             .for_each(|x_, k1_| *x_ += k1_.mul_real(dt_6));
@@ -172,6 +187,500 @@ impl<F: SemiImplicit> TimeEvolution for DiagRK4<F> {
         Zip::from(&mut *k4).and(&self.x).for_each(|k4, &x_| {
             *k4 = x_ + k4.mul_real(dt_6);
         });
+        self.t += dt;
         k4
     }
 }
+
+/// Convert `A::Complex` into `A`, dropping the imaginary part if `A` is real
+fn from_complex<A: Scalar>(c: A::Complex) -> A {
+    match imaginary_unit::<A>() {
+        Some(i) => A::from_real(c.re()) + i.mul_real(c.im()),
+        None => A::from_real(c.re()),
+    }
+}
+
+/// Number of points on the contour for evaluating $\phi$-functions
+const CONTOUR_POINTS: usize = 32;
+
+/// Evaluate $g(z)$ by the mean value on the unit circle around $z$
+///
+/// This avoids the cancellation errors of $\phi$-functions, e.g. $(e^z - 1)/z$,
+/// for small $|z|$ as proposed by Kassam and Trefethen.
+fn contour_mean<A, G>(z: A, g: G) -> A
+where
+    A: Scalar,
+    G: Fn(A::Complex) -> A::Complex,
+{
+    let z = z.as_c();
+    let mut sum = A::complex(0.0, 0.0);
+    for j in 0..CONTOUR_POINTS {
+        let theta = 2.0 * std::f64::consts::PI * (j as f64 + 0.5) / CONTOUR_POINTS as f64;
+        sum += g(z + A::complex(theta.cos(), theta.sin()));
+    }
+    from_complex(sum.div_real(A::real(CONTOUR_POINTS as f64)))
+}
+
+/// $\phi_1(z) = (e^z - 1) / z$
+fn phi1<A: Scalar>(z: A) -> A {
+    contour_mean(z, |z| (z.exp() - A::complex(1.0, 0.0)) / z)
+}
+
+/// $\phi_2(z) = (e^z - 1 - z) / z^2$
+fn phi2<A: Scalar>(z: A) -> A {
+    contour_mean(z, |z| (z.exp() - A::complex(1.0, 0.0) - z) / (z * z))
+}
+
+#[cfg_attr(doc, katexit::katexit)]
+/// Exponential time differencing scheme of 1st order
+///
+/// $$
+/// x_{n+1} = e^{A\Delta t} x_n + \Delta t \phi_1(A\Delta t) f(x_n, t_n)
+/// $$
+/// where $\phi_1(z) = (e^z - 1)/z$.
+///
+/// Links
+/// -----
+/// - ["Exponential time differencing for stiff systems", S. M. Cox and P. C. Matthews, J. Comput. Phys. 176, 430 (2002)](https://doi.org/10.1006/jcph.2002.6995)
+///
+#[derive(Debug, Clone)]
+pub struct ETD1<F: NonAutonomousSemiImplicit> {
+    nlin: F,
+    t: <F::Scalar as Scalar>::Real,
+    dt: <F::Scalar as Scalar>::Real,
+    diag: Array<F::Scalar, F::Dim>,
+    e: Array<F::Scalar, F::Dim>,
+    p1: Array<F::Scalar, F::Dim>,
+    nx: Array<F::Scalar, F::Dim>,
+}
+
+impl<F: NonAutonomousSemiImplicit> ETD1<F> {
+    fn update_coefficients(&mut self) {
+        let dt = self.dt;
+        Zip::from(&mut self.e)
+            .and(&mut self.p1)
+            .and(&self.diag)
+            .for_each(|e, p1, &d| {
+                let z = d.mul_real(dt);
+                *e = z.exp();
+                *p1 = phi1(z).mul_real(dt);
+            });
+    }
+}
+
+impl<F: NonAutonomousSemiImplicit> Scheme for ETD1<F> {
+    type Core = F;
+    fn new(nlin: F, dt: Self::Time) -> Self {
+        let diag = nlin.lin_diag();
+        let e = Array::zeros(diag.raw_dim());
+        let p1 = Array::zeros(diag.raw_dim());
+        let nx = Array::zeros(diag.raw_dim());
+        let mut etd = ETD1 {
+            nlin,
+            t: F::Scalar::real(0.0),
+            dt,
+            diag,
+            e,
+            p1,
+            nx,
+        };
+        etd.update_coefficients();
+        etd
+    }
+    fn core(&self) -> &Self::Core {
+        &self.nlin
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.nlin
+    }
+}
+
+impl<F: NonAutonomousSemiImplicit> TimeStep for ETD1<F> {
+    type Time = <F::Scalar as Scalar>::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
+        self.update_coefficients();
+    }
+}
+
+impl<F: NonAutonomousSemiImplicit> CurrentTime for ETD1<F> {
+    fn get_time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+}
+
+impl<F: NonAutonomousSemiImplicit> ModelSpec for ETD1<F> {
+    type Scalar = F::Scalar;
+    type Dim = F::Dim;
+
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.nlin.model_size()
+    }
+}
+
+impl<F: NonAutonomousSemiImplicit> TimeEvolution for ETD1<F> {
+    fn iterate<'a, S>(
+        &mut self,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        self.nx.zip_mut_with(x, |buf, x| *buf = *x);
+        self.nlin.nlin_t(self.t, &mut self.nx);
+        Zip::from(&mut *x)
+            .and(&self.nx)
+            .and(&self.e)
+            .and(&self.p1)
+            .for_each(|x, &nx, &e, &p1| {
+                *x = e * *x + p1 * nx;
+            });
+        self.t += self.dt;
+        x
+    }
+}
+
+#[cfg_attr(doc, katexit::katexit)]
+/// Exponential time differencing scheme of 2nd order (ETD2RK)
+///
+/// $$
+/// \begin{align*}
+/// a_n &= e^{A\Delta t} x_n + \Delta t \phi_1(A\Delta t) f(x_n, t_n) \\\\
+/// x_{n+1} &= a_n + \Delta t \phi_2(A\Delta t) \left(f(a_n, t_n + \Delta t) - f(x_n, t_n) \right)
+/// \end{align*}
+/// $$
+/// where $\phi_2(z) = (e^z - 1 - z)/z^2$.
+///
+/// Links
+/// -----
+/// - ["Exponential time differencing for stiff systems", S. M. Cox and P. C. Matthews, J. Comput. Phys. 176, 430 (2002)](https://doi.org/10.1006/jcph.2002.6995)
+///
+#[derive(Debug, Clone)]
+pub struct ETDRK2<F: NonAutonomousSemiImplicit> {
+    nlin: F,
+    t: <F::Scalar as Scalar>::Real,
+    dt: <F::Scalar as Scalar>::Real,
+    diag: Array<F::Scalar, F::Dim>,
+    e: Array<F::Scalar, F::Dim>,
+    p1: Array<F::Scalar, F::Dim>,
+    p2: Array<F::Scalar, F::Dim>,
+    nx: Array<F::Scalar, F::Dim>,
+    na: Array<F::Scalar, F::Dim>,
+}
+
+impl<F: NonAutonomousSemiImplicit> ETDRK2<F> {
+    fn update_coefficients(&mut self) {
+        let dt = self.dt;
+        Zip::from(&mut self.e)
+            .and(&mut self.p1)
+            .and(&mut self.p2)
+            .and(&self.diag)
+            .for_each(|e, p1, p2, &d| {
+                let z = d.mul_real(dt);
+                *e = z.exp();
+                *p1 = phi1(z).mul_real(dt);
+                *p2 = phi2(z).mul_real(dt);
+            });
+    }
+}
+
+impl<F: NonAutonomousSemiImplicit> Scheme for ETDRK2<F> {
+    type Core = F;
+    fn new(nlin: F, dt: Self::Time) -> Self {
+        let diag = nlin.lin_diag();
+        let e = Array::zeros(diag.raw_dim());
+        let p1 = Array::zeros(diag.raw_dim());
+        let p2 = Array::zeros(diag.raw_dim());
+        let nx = Array::zeros(diag.raw_dim());
+        let na = Array::zeros(diag.raw_dim());
+        let mut etd = ETDRK2 {
+            nlin,
+            t: F::Scalar::real(0.0),
+            dt,
+            diag,
+            e,
+            p1,
+            p2,
+            nx,
+            na,
+        };
+        etd.update_coefficients();
+        etd
+    }
+    fn core(&self) -> &Self::Core {
+        &self.nlin
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.nlin
+    }
+}
+
+impl<F: NonAutonomousSemiImplicit> TimeStep for ETDRK2<F> {
+    type Time = <F::Scalar as Scalar>::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
+        self.update_coefficients();
+    }
+}
+
+impl<F: NonAutonomousSemiImplicit> CurrentTime for ETDRK2<F> {
+    fn get_time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+}
+
+impl<F: NonAutonomousSemiImplicit> ModelSpec for ETDRK2<F> {
+    type Scalar = F::Scalar;
+    type Dim = F::Dim;
+
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.nlin.model_size()
+    }
+}
+
+impl<F: NonAutonomousSemiImplicit> TimeEvolution for ETDRK2<F> {
+    fn iterate<'a, S>(
+        &mut self,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let t = self.t;
+        let dt = self.dt;
+        self.nx.zip_mut_with(x, |buf, x| *buf = *x);
+        self.nlin.nlin_t(t, &mut self.nx);
+        // a
+        Zip::from(&mut *x)
+            .and(&self.nx)
+            .and(&self.e)
+            .and(&self.p1)
+            .for_each(|x, &nx, &e, &p1| {
+                *x = e * *x + p1 * nx;
+            });
+        self.na.zip_mut_with(x, |buf, a| *buf = *a);
+        self.nlin.nlin_t(t + dt, &mut self.na);
+        Zip::from(&mut *x)
+            .and(&self.nx)
+            .and(&self.na)
+            .and(&self.p2)
+            .for_each(|x, &nx, &na, &p2| {
+                *x += p2 * (na - nx);
+            });
+        self.t += dt;
+        x
+    }
+}
+
+#[cfg_attr(doc, katexit::katexit)]
+/// Exponential time differencing scheme of 4th order (ETDRK4)
+///
+/// The scheme proposed by Cox and Matthews,
+/// whose coefficients are evaluated by the contour integral method of Kassam and Trefethen
+/// to avoid cancellation errors for small $|A \Delta t|$.
+/// Compared to [DiagRK4], which is an integrating factor method,
+/// this scheme treats the stiff linear part more accurately.
+///
+/// Links
+/// -----
+/// - ["Exponential time differencing for stiff systems", S. M. Cox and P. C. Matthews, J. Comput. Phys. 176, 430 (2002)](https://doi.org/10.1006/jcph.2002.6995)
+/// - ["Fourth-order time-stepping for stiff PDEs", A.-K. Kassam and L. N. Trefethen, SIAM J. Sci. Comput. 26, 1214 (2005)](https://doi.org/10.1137/S1064827502410633)
+///
+#[derive(Debug, Clone)]
+pub struct ETDRK4<F: NonAutonomousSemiImplicit> {
+    nlin: F,
+    t: <F::Scalar as Scalar>::Real,
+    dt: <F::Scalar as Scalar>::Real,
+    diag: Array<F::Scalar, F::Dim>,
+    e: Array<F::Scalar, F::Dim>,
+    e2: Array<F::Scalar, F::Dim>,
+    q: Array<F::Scalar, F::Dim>,
+    f1: Array<F::Scalar, F::Dim>,
+    f2: Array<F::Scalar, F::Dim>,
+    f3: Array<F::Scalar, F::Dim>,
+    nx: Array<F::Scalar, F::Dim>,
+    a: Array<F::Scalar, F::Dim>,
+    na: Array<F::Scalar, F::Dim>,
+    nb: Array<F::Scalar, F::Dim>,
+    nc: Array<F::Scalar, F::Dim>,
+}
+
+impl<F: NonAutonomousSemiImplicit> ETDRK4<F> {
+    fn update_coefficients(&mut self) {
+        let dt = self.dt;
+        let c = |x: f64| <F::Scalar as Scalar>::complex(x, 0.0);
+        Zip::from(&mut self.e)
+            .and(&mut self.e2)
+            .and(&mut self.q)
+            .and(&self.diag)
+            .for_each(|e, e2, q, &d| {
+                let z = d.mul_real(dt);
+                *e = z.exp();
+                *e2 = z.div_real(F::Scalar::real(2.0)).exp();
+                *q = contour_mean(z, |z| ((z / c(2.0)).exp() - c(1.0)) / z).mul_real(dt);
+            });
+        Zip::from(&mut self.f1)
+            .and(&mut self.f2)
+            .and(&mut self.f3)
+            .and(&self.diag)
+            .for_each(|f1, f2, f3, &d| {
+                let z = d.mul_real(dt);
+                *f1 = contour_mean(z, |z| {
+                    (c(-4.0) - z + z.exp() * (c(4.0) - c(3.0) * z + z * z)) / (z * z * z)
+                })
+                .mul_real(dt);
+                *f2 = contour_mean(z, |z| (c(2.0) + z + z.exp() * (c(-2.0) + z)) / (z * z * z))
+                    .mul_real(dt);
+                *f3 = contour_mean(z, |z| {
+                    (c(-4.0) - c(3.0) * z - z * z + z.exp() * (c(4.0) - z)) / (z * z * z)
+                })
+                .mul_real(dt);
+            });
+    }
+}
+
+impl<F: NonAutonomousSemiImplicit> Scheme for ETDRK4<F> {
+    type Core = F;
+    fn new(nlin: F, dt: Self::Time) -> Self {
+        let diag = nlin.lin_diag();
+        let zeros = || Array::zeros(diag.raw_dim());
+        let mut etd = ETDRK4 {
+            e: zeros(),
+            e2: zeros(),
+            q: zeros(),
+            f1: zeros(),
+            f2: zeros(),
+            f3: zeros(),
+            nx: zeros(),
+            a: zeros(),
+            na: zeros(),
+            nb: zeros(),
+            nc: zeros(),
+            nlin,
+            t: F::Scalar::real(0.0),
+            dt,
+            diag,
+        };
+        etd.update_coefficients();
+        etd
+    }
+    fn core(&self) -> &Self::Core {
+        &self.nlin
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.nlin
+    }
+}
+
+impl<F: NonAutonomousSemiImplicit> TimeStep for ETDRK4<F> {
+    type Time = <F::Scalar as Scalar>::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
+        self.update_coefficients();
+    }
+}
+
+impl<F: NonAutonomousSemiImplicit> CurrentTime for ETDRK4<F> {
+    fn get_time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+}
+
+impl<F: NonAutonomousSemiImplicit> ModelSpec for ETDRK4<F> {
+    type Scalar = F::Scalar;
+    type Dim = F::Dim;
+
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.nlin.model_size()
+    }
+}
+
+impl<F: NonAutonomousSemiImplicit> TimeEvolution for ETDRK4<F> {
+    fn iterate<'a, S>(
+        &mut self,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let two = F::Scalar::real(2.0);
+        let t = self.t;
+        let dt = self.dt;
+        let dt_2 = dt / two;
+        // N(x)
+        self.nx.zip_mut_with(x, |buf, x| *buf = *x);
+        self.nlin.nlin_t(t, &mut self.nx);
+        // a = E_2 x + Q N(x)
+        Zip::from(&mut self.a)
+            .and(&*x)
+            .and(&self.nx)
+            .and(&self.e2)
+            .and(&self.q)
+            .for_each(|a, &x, &nx, &e2, &q| *a = e2 * x + q * nx);
+        self.na.zip_mut_with(&self.a, |buf, a| *buf = *a);
+        self.nlin.nlin_t(t + dt_2, &mut self.na);
+        // b = E_2 x + Q N(a)
+        Zip::from(&mut self.nb)
+            .and(&*x)
+            .and(&self.na)
+            .and(&self.e2)
+            .and(&self.q)
+            .for_each(|b, &x, &na, &e2, &q| *b = e2 * x + q * na);
+        self.nlin.nlin_t(t + dt_2, &mut self.nb);
+        // c = E_2 a + Q (2N(b) - N(x))
+        Zip::from(&mut self.nc)
+            .and(&self.a)
+            .and(&self.nx)
+            .and(&self.nb)
+            .and(&self.e2)
+            .and(&self.q)
+            .for_each(|c, &a, &nx, &nb, &e2, &q| *c = e2 * a + q * (nb.mul_real(two) - nx));
+        self.nlin.nlin_t(t + dt, &mut self.nc);
+        // x = E x + f_1 N(x) + 2 f_2 (N(a) + N(b)) + f_3 N(c)
+        Zip::from(&mut *x)
+            .and(&self.nx)
+            .and(&self.nc)
+            .and(&self.e)
+            .and(&self.f1)
+            .and(&self.f3)
+            .for_each(|x, &nx, &nc, &e, &f1, &f3| {
+                *x = e * *x + f1 * nx + f3 * nc;
+            });
+        Zip::from(&mut *x)
+            .and(&self.na)
+            .and(&self.nb)
+            .and(&self.f2)
+            .for_each(|x, &na, &nb, &f2| {
+                *x += f2 * (na + nb).mul_real(two);
+            });
+        self.t += dt;
+        x
+    }
+}
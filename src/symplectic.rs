@@ -0,0 +1,493 @@
+//! symplectic schemes for separable Hamiltonian systems
+//!
+//! All schemes in this module are compositions of two exactly solvable flows,
+//! "kick" $p \gets p - c \Delta t \partial V / \partial q$ and
+//! "drift" $q \gets q + c \Delta t \partial T / \partial p$,
+//! and thus preserve the symplectic structure of [SeparableHamiltonian].
+//! The state is the concatenated $(q, p)$ as documented in [Hamiltonian].
+
+use ndarray::*;
+use ndarray_linalg::*;
+
+use super::traits::*;
+
+/// Split concatenated state into $(q, p)$
+fn split<A, S, D>(x: &mut ArrayBase<S, D>) -> (ArrayViewMut<'_, A, D>, ArrayViewMut<'_, A, D>)
+where
+    S: DataMut<Elem = A>,
+    D: Dimension,
+{
+    let n = x.len_of(Axis(0)) / 2;
+    x.view_mut().split_at(Axis(0), n)
+}
+
+/// Buffer for $q$ or $p$, i.e. the half of the state
+fn half_zeros<H: SeparableHamiltonian>(f: &H) -> Array<H::Scalar, H::Dim> {
+    let x: Array<H::Scalar, H::Dim> = Array::zeros(f.model_size());
+    let n = x.len_of(Axis(0)) / 2;
+    x.slice_axis(Axis(0), Slice::from(..n)).to_owned()
+}
+
+/// $p \gets p - c \partial V / \partial q$
+fn kick<H: SeparableHamiltonian>(
+    f: &mut H,
+    buf: &mut Array<H::Scalar, H::Dim>,
+    q: &ArrayViewMut<H::Scalar, H::Dim>,
+    p: &mut ArrayViewMut<H::Scalar, H::Dim>,
+    c: <H::Scalar as Scalar>::Real,
+) {
+    buf.zip_mut_with(q, |buf, q| *buf = *q);
+    f.dh_dq(buf, p);
+    p.zip_mut_with(buf, |p, dv| *p -= dv.mul_real(c));
+}
+
+/// $q \gets q + c \partial T / \partial p$
+fn drift<H: SeparableHamiltonian>(
+    f: &mut H,
+    buf: &mut Array<H::Scalar, H::Dim>,
+    q: &mut ArrayViewMut<H::Scalar, H::Dim>,
+    p: &ArrayViewMut<H::Scalar, H::Dim>,
+    c: <H::Scalar as Scalar>::Real,
+) {
+    buf.zip_mut_with(p, |buf, p| *buf = *p);
+    f.dh_dp(q, buf);
+    q.zip_mut_with(buf, |q, dt| *q += dt.mul_real(c));
+}
+
+/// Störmer-Verlet step with a step size `dt`
+fn verlet<H: SeparableHamiltonian>(
+    f: &mut H,
+    buf: &mut Array<H::Scalar, H::Dim>,
+    q: &mut ArrayViewMut<H::Scalar, H::Dim>,
+    p: &mut ArrayViewMut<H::Scalar, H::Dim>,
+    dt: <H::Scalar as Scalar>::Real,
+) {
+    let dt_2 = dt / H::Scalar::real(2.0);
+    kick(f, buf, q, p, dt_2);
+    drift(f, buf, q, p, dt);
+    kick(f, buf, q, p, dt_2);
+}
+
+/// Symmetric composition of Störmer-Verlet steps with weights `w`
+fn compose<H: SeparableHamiltonian>(
+    f: &mut H,
+    buf: &mut Array<H::Scalar, H::Dim>,
+    q: &mut ArrayViewMut<H::Scalar, H::Dim>,
+    p: &mut ArrayViewMut<H::Scalar, H::Dim>,
+    w: &[f64],
+    dt: <H::Scalar as Scalar>::Real,
+) {
+    for &w in w.iter() {
+        verlet(f, buf, q, p, dt * H::Scalar::real(w));
+    }
+}
+
+/// Symplectic Euler scheme (1st order)
+///
+/// $p$ is updated first using $q_n$, and then $q$ is updated using $p_{n+1}$.
+#[derive(Debug, Clone)]
+pub struct SymplecticEuler<H: SeparableHamiltonian> {
+    f: H,
+    t: <H::Scalar as Scalar>::Real,
+    dt: <H::Scalar as Scalar>::Real,
+    buf: Array<H::Scalar, H::Dim>,
+}
+
+impl<A: Scalar, H: SeparableHamiltonian<Scalar = A>> TimeStep for SymplecticEuler<H> {
+    type Time = A::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
+    }
+}
+
+impl<A: Scalar, H: SeparableHamiltonian<Scalar = A>> CurrentTime for SymplecticEuler<H> {
+    fn get_time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+}
+
+impl<H: SeparableHamiltonian> Scheme for SymplecticEuler<H> {
+    type Core = H;
+    fn new(f: H, dt: Self::Time) -> Self {
+        let buf = half_zeros(&f);
+        let t = H::Scalar::real(0.0);
+        Self { f, t, dt, buf }
+    }
+    fn core(&self) -> &Self::Core {
+        &self.f
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.f
+    }
+}
+
+impl<H: SeparableHamiltonian> ModelSpec for SymplecticEuler<H> {
+    type Scalar = H::Scalar;
+    type Dim = H::Dim;
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.f.model_size()
+    }
+}
+
+impl<H: SeparableHamiltonian> TimeEvolution for SymplecticEuler<H> {
+    fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, H::Dim>) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let (mut q, mut p) = split(x);
+        kick(&mut self.f, &mut self.buf, &q, &mut p, self.dt);
+        drift(&mut self.f, &mut self.buf, &mut q, &p, self.dt);
+        self.t += self.dt;
+        x
+    }
+}
+
+/// Störmer-Verlet, or leapfrog scheme (2nd order)
+///
+/// This is implemented in the "kick-drift-kick" form.
+#[derive(Debug, Clone)]
+pub struct Leapfrog<H: SeparableHamiltonian> {
+    f: H,
+    t: <H::Scalar as Scalar>::Real,
+    dt: <H::Scalar as Scalar>::Real,
+    buf: Array<H::Scalar, H::Dim>,
+}
+
+/// Alias of [Leapfrog]
+pub type StormerVerlet<H> = Leapfrog<H>;
+
+impl<A: Scalar, H: SeparableHamiltonian<Scalar = A>> TimeStep for Leapfrog<H> {
+    type Time = A::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
+    }
+}
+
+impl<A: Scalar, H: SeparableHamiltonian<Scalar = A>> CurrentTime for Leapfrog<H> {
+    fn get_time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+}
+
+impl<H: SeparableHamiltonian> Scheme for Leapfrog<H> {
+    type Core = H;
+    fn new(f: H, dt: Self::Time) -> Self {
+        let buf = half_zeros(&f);
+        let t = H::Scalar::real(0.0);
+        Self { f, t, dt, buf }
+    }
+    fn core(&self) -> &Self::Core {
+        &self.f
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.f
+    }
+}
+
+impl<H: SeparableHamiltonian> ModelSpec for Leapfrog<H> {
+    type Scalar = H::Scalar;
+    type Dim = H::Dim;
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.f.model_size()
+    }
+}
+
+impl<H: SeparableHamiltonian> TimeEvolution for Leapfrog<H> {
+    fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, H::Dim>) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let (mut q, mut p) = split(x);
+        verlet(&mut self.f, &mut self.buf, &mut q, &mut p, self.dt);
+        self.t += self.dt;
+        x
+    }
+}
+
+/// $w_1, w_0, w_1$ with $w_1 = 1 / (2 - 2^{1/3})$ and $w_0 = 1 - 2w_1$
+const FOREST_RUTH: [f64; 3] = [1.3512071919596578, -1.7024143839193155, 1.3512071919596578];
+
+/// Forest-Ruth scheme (4th order)
+///
+/// Composition of three Störmer-Verlet steps.
+///
+/// Links
+/// -----
+/// - E. Forest and R. D. Ruth, "Fourth-order symplectic integration", Physica D 43, 105 (1990)
+#[derive(Debug, Clone)]
+pub struct ForestRuth<H: SeparableHamiltonian> {
+    f: H,
+    t: <H::Scalar as Scalar>::Real,
+    dt: <H::Scalar as Scalar>::Real,
+    buf: Array<H::Scalar, H::Dim>,
+}
+
+impl<A: Scalar, H: SeparableHamiltonian<Scalar = A>> TimeStep for ForestRuth<H> {
+    type Time = A::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
+    }
+}
+
+impl<A: Scalar, H: SeparableHamiltonian<Scalar = A>> CurrentTime for ForestRuth<H> {
+    fn get_time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+}
+
+impl<H: SeparableHamiltonian> Scheme for ForestRuth<H> {
+    type Core = H;
+    fn new(f: H, dt: Self::Time) -> Self {
+        let buf = half_zeros(&f);
+        let t = H::Scalar::real(0.0);
+        Self { f, t, dt, buf }
+    }
+    fn core(&self) -> &Self::Core {
+        &self.f
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.f
+    }
+}
+
+impl<H: SeparableHamiltonian> ModelSpec for ForestRuth<H> {
+    type Scalar = H::Scalar;
+    type Dim = H::Dim;
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.f.model_size()
+    }
+}
+
+impl<H: SeparableHamiltonian> TimeEvolution for ForestRuth<H> {
+    fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, H::Dim>) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let (mut q, mut p) = split(x);
+        compose(
+            &mut self.f,
+            &mut self.buf,
+            &mut q,
+            &mut p,
+            &FOREST_RUTH,
+            self.dt,
+        );
+        self.t += self.dt;
+        x
+    }
+}
+
+/// Solution A of Yoshida (1990), $w_3, w_2, w_1, w_0, w_1, w_2, w_3$
+const YOSHIDA6: [f64; 7] = [
+    0.784513610477560,
+    0.235573213359357,
+    -1.17767998417887,
+    1.3151863206839063,
+    -1.17767998417887,
+    0.235573213359357,
+    0.784513610477560,
+];
+
+/// Yoshida's 6th order scheme
+///
+/// Composition of seven Störmer-Verlet steps.
+///
+/// Links
+/// -----
+/// - H. Yoshida, "Construction of higher order symplectic integrators", Phys. Lett. A 150, 262 (1990)
+#[derive(Debug, Clone)]
+pub struct Yoshida6<H: SeparableHamiltonian> {
+    f: H,
+    t: <H::Scalar as Scalar>::Real,
+    dt: <H::Scalar as Scalar>::Real,
+    buf: Array<H::Scalar, H::Dim>,
+}
+
+impl<A: Scalar, H: SeparableHamiltonian<Scalar = A>> TimeStep for Yoshida6<H> {
+    type Time = A::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
+    }
+}
+
+impl<A: Scalar, H: SeparableHamiltonian<Scalar = A>> CurrentTime for Yoshida6<H> {
+    fn get_time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+}
+
+impl<H: SeparableHamiltonian> Scheme for Yoshida6<H> {
+    type Core = H;
+    fn new(f: H, dt: Self::Time) -> Self {
+        let buf = half_zeros(&f);
+        let t = H::Scalar::real(0.0);
+        Self { f, t, dt, buf }
+    }
+    fn core(&self) -> &Self::Core {
+        &self.f
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.f
+    }
+}
+
+impl<H: SeparableHamiltonian> ModelSpec for Yoshida6<H> {
+    type Scalar = H::Scalar;
+    type Dim = H::Dim;
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.f.model_size()
+    }
+}
+
+impl<H: SeparableHamiltonian> TimeEvolution for Yoshida6<H> {
+    fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, H::Dim>) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let (mut q, mut p) = split(x);
+        compose(
+            &mut self.f,
+            &mut self.buf,
+            &mut q,
+            &mut p,
+            &YOSHIDA6,
+            self.dt,
+        );
+        self.t += self.dt;
+        x
+    }
+}
+
+/// Solution D of Yoshida (1990), $w_7, \ldots, w_1, w_0, w_1, \ldots, w_7$
+const YOSHIDA8: [f64; 15] = [
+    0.914844246229740,
+    0.253693336566229,
+    -1.44485223686048,
+    -0.158240635368243,
+    1.93813913762276,
+    -1.96061023297549,
+    0.102799849391985,
+    1.7084530707869978,
+    0.102799849391985,
+    -1.96061023297549,
+    1.93813913762276,
+    -0.158240635368243,
+    -1.44485223686048,
+    0.253693336566229,
+    0.914844246229740,
+];
+
+/// Yoshida's 8th order scheme
+///
+/// Composition of fifteen Störmer-Verlet steps.
+///
+/// Links
+/// -----
+/// - H. Yoshida, "Construction of higher order symplectic integrators", Phys. Lett. A 150, 262 (1990)
+#[derive(Debug, Clone)]
+pub struct Yoshida8<H: SeparableHamiltonian> {
+    f: H,
+    t: <H::Scalar as Scalar>::Real,
+    dt: <H::Scalar as Scalar>::Real,
+    buf: Array<H::Scalar, H::Dim>,
+}
+
+impl<A: Scalar, H: SeparableHamiltonian<Scalar = A>> TimeStep for Yoshida8<H> {
+    type Time = A::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
+    }
+}
+
+impl<A: Scalar, H: SeparableHamiltonian<Scalar = A>> CurrentTime for Yoshida8<H> {
+    fn get_time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+}
+
+impl<H: SeparableHamiltonian> Scheme for Yoshida8<H> {
+    type Core = H;
+    fn new(f: H, dt: Self::Time) -> Self {
+        let buf = half_zeros(&f);
+        let t = H::Scalar::real(0.0);
+        Self { f, t, dt, buf }
+    }
+    fn core(&self) -> &Self::Core {
+        &self.f
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.f
+    }
+}
+
+impl<H: SeparableHamiltonian> ModelSpec for Yoshida8<H> {
+    type Scalar = H::Scalar;
+    type Dim = H::Dim;
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.f.model_size()
+    }
+}
+
+impl<H: SeparableHamiltonian> TimeEvolution for Yoshida8<H> {
+    fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, H::Dim>) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let (mut q, mut p) = split(x);
+        compose(
+            &mut self.f,
+            &mut self.buf,
+            &mut q,
+            &mut p,
+            &YOSHIDA8,
+            self.dt,
+        );
+        self.t += self.dt;
+        x
+    }
+}
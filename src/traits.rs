@@ -3,7 +3,7 @@ use ndarray_linalg::*;
 use num_traits::Float;
 
 #[cfg(doc)]
-use crate::{explicit::*, ode::*, semi_implicit::*};
+use crate::{explicit::*, ode::*, semi_implicit::*, symplectic::*};
 
 #[cfg_attr(doc, katexit::katexit)]
 /// Model space, the linear space where the system state is represented.
@@ -28,6 +28,16 @@ pub trait TimeStep {
     fn set_dt(&mut self, dt: Self::Time);
 }
 
+/// Interface for set/get the current time
+///
+/// Schemes advance the current time by each [TimeEvolution::iterate],
+/// and it is passed to the non-autonomous systems,
+/// e.g. [NonAutonomousExplicit] or [NonAutonomousSemiImplicit].
+pub trait CurrentTime: TimeStep {
+    fn get_time(&self) -> Self::Time;
+    fn set_time(&mut self, t: Self::Time);
+}
+
 #[cfg_attr(doc, katexit::katexit)]
 /// Abstraction for implementing explicit schemes
 ///
@@ -100,6 +110,163 @@ pub trait SemiImplicit: ModelSpec {
     fn diag(&self) -> Array<Self::Scalar, Self::Dim>;
 }
 
+#[cfg_attr(doc, katexit::katexit)]
+/// Analytic Jacobian of [Explicit] models
+///
+/// The linearization of $dx/dt = f(x)$ around a trajectory $x(t)$,
+/// $$
+/// \frac{d\delta x}{dt} = J(x) \delta x,\space J = \frac{\partial f}{\partial x}
+/// $$
+/// describes the evolution of infinitesimal perturbations, i.e. tangent vectors.
+/// Schemes implementing [TangentEvolution] with this trait, e.g. [RK4],
+/// evolve tangent vectors without numerical differentiation.
+/// Since this extends [Explicit], the tangent propagation is available only for autonomous models.
+///
+pub trait AnalyticJacobian: Explicit {
+    /// Evaluate $J(x) \delta x$ for a given state $x$ and tangent vector $\delta x$
+    fn jacobian<'a, S, Sx>(
+        &mut self,
+        x: &ArrayBase<Sx, Self::Dim>,
+        dx: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+        Sx: Data<Elem = Self::Scalar>;
+}
+
+#[cfg_attr(doc, katexit::katexit)]
+/// Abstraction for explicit schemes of non-autonomous systems
+///
+/// Consider an initial value problem of ODE whose right hand side depends on time explicitly:
+/// $$
+/// \frac{dx}{dt} = f(x, t),\space x(t_0) = x_0
+/// $$
+/// e.g. a driven Duffing oscillator.
+/// Autonomous systems implementing [Explicit] also implement this trait automatically
+/// by ignoring $t$, and thus explicit schemes like [Euler] are implemented for this trait.
+///
+pub trait NonAutonomousExplicit: ModelSpec {
+    /// Evaluate $f(x, t)$ for a given state $x$ and time $t$
+    fn rhs_t<'a, S>(
+        &mut self,
+        t: <Self::Scalar as Scalar>::Real,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>;
+}
+
+impl<F: Explicit> NonAutonomousExplicit for F {
+    fn rhs_t<'a, S>(
+        &mut self,
+        _t: <Self::Scalar as Scalar>::Real,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        self.rhs(x)
+    }
+}
+
+#[cfg_attr(doc, katexit::katexit)]
+/// Abstraction for semi-implicit schemes of non-autonomous systems
+///
+/// Non-autonomous version of [SemiImplicit], i.e. the equation is in a form
+/// $$
+/// \frac{dx}{dt} = Ax + f(x, t)
+/// $$
+/// where the stiff linear part $A$ is still diagonal and independent of time.
+/// This is implemented automatically for [SemiImplicit] by ignoring $t$.
+///
+pub trait NonAutonomousSemiImplicit: ModelSpec {
+    /// Non-stiff part $f(x, t)$
+    fn nlin_t<'a, S>(
+        &mut self,
+        t: <Self::Scalar as Scalar>::Real,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>;
+    /// Diagonal elements of stiff linear part of $A$
+    fn lin_diag(&self) -> Array<Self::Scalar, Self::Dim>;
+}
+
+impl<F: SemiImplicit> NonAutonomousSemiImplicit for F {
+    fn nlin_t<'a, S>(
+        &mut self,
+        _t: <Self::Scalar as Scalar>::Real,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        self.nlin(x)
+    }
+
+    fn lin_diag(&self) -> Array<Self::Scalar, Self::Dim> {
+        self.diag()
+    }
+}
+
+#[cfg_attr(doc, katexit::katexit)]
+/// Abstraction for Hamiltonian systems
+///
+/// The system state is described by canonical variables $(q, p)$,
+/// which are concatenated along the first axis,
+/// i.e. the first half of the state $x = (q, p)$ is $q$ and the latter is $p$.
+/// [ModelSpec::model_size] returns the size of concatenated state.
+/// The equation of motion is given by the Hamiltonian $H(q, p)$:
+/// $$
+/// \frac{dq}{dt} = \frac{\partial H}{\partial p},
+/// \frac{dp}{dt} = -\frac{\partial H}{\partial q}.
+/// $$
+///
+pub trait Hamiltonian: ModelSpec {
+    /// Value of the Hamiltonian $H(q, p)$, i.e. the energy of the system
+    fn energy<S>(
+        &mut self,
+        q: &ArrayBase<S, Self::Dim>,
+        p: &ArrayBase<S, Self::Dim>,
+    ) -> <Self::Scalar as Scalar>::Real
+    where
+        S: Data<Elem = Self::Scalar>;
+    /// Evaluate $\partial H / \partial q$ at $(q, p)$, overwriting `q`
+    fn dh_dq<'a, S, T>(
+        &mut self,
+        q: &'a mut ArrayBase<S, Self::Dim>,
+        p: &ArrayBase<T, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+        T: Data<Elem = Self::Scalar>;
+    /// Evaluate $\partial H / \partial p$ at $(q, p)$, overwriting `p`
+    fn dh_dp<'a, S, T>(
+        &mut self,
+        q: &ArrayBase<T, Self::Dim>,
+        p: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+        T: Data<Elem = Self::Scalar>;
+}
+
+#[cfg_attr(doc, katexit::katexit)]
+/// Abstraction for implementing symplectic schemes
+///
+/// Hamiltonian $H(q, p)$ is separable if it is a sum of the kinetic energy $T(p)$
+/// and the potential $V(q)$:
+/// $$
+/// H(q, p) = T(p) + V(q).
+/// $$
+/// Explicit symplectic schemes, e.g. [SymplecticEuler] or [Leapfrog],
+/// are available for this case.
+///
+/// This is a marker trait, and an implementation promises that [Hamiltonian::dh_dq]
+/// $= \partial V / \partial q$ does not depend on `p`
+/// and [Hamiltonian::dh_dp] $= \partial T / \partial p$ does not depend on `q`.
+pub trait SeparableHamiltonian: Hamiltonian {}
+
 /// Time-evolution operator
 pub trait TimeEvolution: ModelSpec + TimeStep {
     /// calculate next step
@@ -126,6 +293,44 @@ pub trait TimeEvolution: ModelSpec + TimeStep {
     }
 }
 
+/// Time-evolution operator with continuous interpolation of the last step
+///
+/// The dense output is disabled by default since it may require additional evaluations
+/// of the equation for each step.
+pub trait DenseOutput: TimeEvolution + CurrentTime {
+    /// Enable or disable the dense output
+    fn set_dense_output(&mut self, dense: bool);
+
+    /// Length of the last step, i.e. the last step is from `get_time() - last_dt()` to `get_time()`
+    fn last_dt(&self) -> Self::Time;
+
+    /// Evaluate the state at `theta * last_dt()` after the beginning of the last step
+    ///
+    /// `theta` is expected in $[0, 1]$. This panics if the dense output is disabled.
+    fn dense_output<'a, S>(
+        &self,
+        theta: Self::Time,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>;
+}
+
+/// Time-evolution operator which also evolves tangent vectors by its tangent-linear operator
+pub trait TangentEvolution: TimeEvolution {
+    /// calculate next step of `x`, and evolve tangent vectors at `x`
+    /// stored in the columns (the last axis) of `dx`
+    fn iterate_tangent<'a, S, St>(
+        &mut self,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+        dx: &mut ArrayBase<St, <Self::Dim as Dimension>::Larger>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+        St: DataMut<Elem = Self::Scalar>,
+        <Self::Dim as Dimension>::Larger: RemoveAxis + Dimension<Smaller = Self::Dim>;
+}
+
 /// Time evolution schemes
 pub trait Scheme: TimeEvolution {
     type Core: ModelSpec<Scalar = Self::Scalar, Dim = Self::Dim>;
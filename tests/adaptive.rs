@@ -0,0 +1,159 @@
+use ndarray::*;
+use ndarray_linalg::*;
+use std::cell::Cell;
+use std::rc::Rc;
+
+use eom::*;
+
+mod common;
+use common::*;
+
+#[test]
+fn dp45_decay() {
+    let mut teo = explicit::DormandPrince45::new(Decay(2), 0.1);
+    teo.set_tolerance(1e-10, 1e-10);
+    let mut x = arr1(&[1.0, 2.0]);
+    let mut t = 0.0;
+    while t < 2.0 {
+        teo.iterate(&mut x);
+        t += teo.last_dt();
+    }
+    let ans = arr1(&[1.0, 2.0]) * (-t).exp();
+    close_l2(&x, &ans, 1e-8);
+}
+
+/// [Decay] counting the evaluations of the right hand side
+#[derive(Clone, Default)]
+struct Counted(Rc<Cell<usize>>);
+
+impl ModelSpec for Counted {
+    type Scalar = f64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        2
+    }
+}
+
+impl Explicit for Counted {
+    fn rhs<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        self.0.set(self.0.get() + 1);
+        Decay(2).rhs(x)
+    }
+}
+
+#[test]
+fn dp45_fsal() {
+    let eom = Counted::default();
+    let count = eom.0.clone();
+    let mut teo = explicit::DormandPrince45::new(eom, 0.1);
+    let mut x = arr1(&[1.0, 2.0]);
+    for _ in 0..10 {
+        teo.iterate(&mut x);
+    }
+    assert_eq!(teo.rejected(), 0);
+    assert_eq!(count.get(), 1 + 6 * 10);
+
+    // the first stage is evaluated again if the state is modified
+    x[0] = 0.5;
+    teo.iterate(&mut x);
+    assert_eq!(count.get(), 1 + 6 * 10 + 7);
+}
+
+#[test]
+fn dp45_reject() {
+    let eom = ode::Lorenz63::new(10.0, 200.0, 8.0 / 3.0);
+    let mut teo = explicit::DormandPrince45::new(eom, 1.0);
+    let mut x = arr1(&[1.0, 0.0, 0.0]);
+    for _ in 0..100 {
+        teo.iterate(&mut x);
+    }
+    assert!(teo.rejected() > 0);
+    assert!(x.iter().all(|x| x.is_finite()));
+    assert!(teo.get_dt() < 1.0);
+}
+
+#[test]
+fn dop853_decay() {
+    let mut teo = explicit::DOP853::new(Decay(2), 0.1);
+    teo.set_dense_output(true);
+    let mut x = arr1(&[1.0, 2.0]);
+    let mut t = 0.0;
+    while t < 2.0 {
+        teo.iterate(&mut x);
+        t += teo.last_dt();
+    }
+    let ans = arr1(&[1.0, 2.0]) * (-t).exp();
+    close_l2(&x, &ans, 1e-7);
+
+    let mut mid = Array::zeros(2);
+    teo.dense_output(0.5, &mut mid);
+    let ans = arr1(&[1.0, 2.0]) * (-t + 0.5 * teo.last_dt()).exp();
+    close_l2(&mid, &ans, 1e-8);
+}
+
+#[test]
+fn dop853_order() {
+    let mut teo = explicit::DOP853::new(Decay(2), 1.0);
+    teo.set_adaptive(false);
+    let acc = adaptor::accuracy(teo, arr1(&[1.0, 2.0]), 0.5, 4, 3);
+    let rate = acc[0].1 / acc[1].1;
+    assert!(rate > 128.0 && rate < 512.0, "rate = {}", rate);
+}
+
+#[test]
+fn dop853_dt_min() {
+    // the tolerance cannot be satisfied with the lower limit of the step size
+    let eom = ode::Lorenz63::new(10.0, 200.0, 8.0 / 3.0);
+    let mut teo = explicit::DOP853::new(eom, 0.5);
+    teo.set_tolerance(1e-14, 1e-14);
+    teo.set_dt_limits(0.01, 0.5);
+    let mut x = arr1(&[1.0, 0.0, 0.0]);
+    for _ in 0..10 {
+        teo.iterate(&mut x);
+        assert_eq!(teo.last_dt(), 0.01);
+    }
+    assert!(teo.forced() >= 9);
+    assert!(x.iter().all(|x| x.is_finite()));
+}
+
+#[test]
+fn dop853_set_dt_limits() {
+    let mut teo = explicit::DOP853::new(Decay(2), 0.1);
+    teo.set_dt_limits(0.01, 0.5);
+    teo.set_dt(1.0);
+    assert_eq!(teo.get_dt(), 0.5);
+    teo.set_dt(1e-3);
+    assert_eq!(teo.get_dt(), 0.01);
+}
+
+#[test]
+fn dop853_non_finite() {
+    // NaN is not accepted at the lower limit of the step size
+    let mut teo = explicit::DOP853::new(Singular, 0.1);
+    teo.set_dt_limits(0.01, 0.5);
+    let mut x = arr1(&[1.0, 2.0]);
+    assert!(matches!(
+        teo.try_iterate(&mut x),
+        Err(eom::Error::StepUnderflow)
+    ));
+    assert_eq!(x, arr1(&[1.0, 2.0]));
+    assert_eq!(teo.get_time(), 0.0);
+    assert_eq!(teo.get_dt(), 0.1);
+    assert_eq!(teo.forced(), 0);
+}
+
+#[test]
+fn dp45_underflow() {
+    let mut teo = explicit::DormandPrince45::new(Singular, 0.1);
+    let mut x = arr1(&[1.0, 2.0]);
+    assert!(matches!(
+        teo.try_iterate(&mut x),
+        Err(eom::Error::StepUnderflow)
+    ));
+    assert_eq!(x, arr1(&[1.0, 2.0]));
+    assert_eq!(teo.get_time(), 0.0);
+    assert_eq!(teo.get_dt(), 0.1);
+}
@@ -0,0 +1,76 @@
+use ndarray::*;
+
+use eom::*;
+
+/// $dx/dt = x^2$, which diverges at $t = 1/x_0$
+#[derive(Clone, Copy)]
+struct BlowUp;
+
+impl ModelSpec for BlowUp {
+    type Scalar = f64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        1
+    }
+}
+
+impl Explicit for BlowUp {
+    fn rhs<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        x.mapv_inplace(|x| x * x);
+        x
+    }
+}
+
+#[test]
+fn checked_non_finite() {
+    let mut teo = adaptor::checked(explicit::Euler::new(BlowUp, 0.1));
+    let mut x = arr1(&[1.0]);
+    let err = teo.try_iterate_n(&mut x, 1000).unwrap_err();
+    assert_eq!(err.violation, adaptor::Violation::NonFinite);
+    assert_eq!(err.step, teo.step());
+    assert!(err.last[0].is_finite());
+    // state is restored to the last finite state
+    assert_eq!(x, err.last);
+}
+
+#[test]
+fn checked_norm_bound() {
+    let mut teo = adaptor::checked(explicit::RK4::new(BlowUp, 0.01));
+    teo.set_norm_bound(10.0);
+    let mut x = arr1(&[1.0]);
+    let err = teo.try_iterate_n(&mut x, 1000).unwrap_err();
+    match err.violation {
+        adaptor::Violation::NormBound { norm, bound } => {
+            assert!(norm > 10.0);
+            assert_eq!(bound, 10.0);
+        }
+        _ => panic!("Unexpected violation: {}", err),
+    }
+    // exact solution 1 / (1 - t) exceeds 10 at t = 0.9
+    assert_eq!(err.step, 90);
+    assert!(err.last[0] <= 10.0);
+}
+
+#[test]
+fn checked_time_series() {
+    let mut teo = adaptor::checked(explicit::Euler::new(BlowUp, 0.1));
+    let ts: Vec<_> = adaptor::checked_time_series(arr1(&[1.0]), &mut teo)
+        .take(1000)
+        .collect();
+    // time series ends just after the error
+    assert!(ts.len() < 1000);
+    let (last, init) = ts.split_last().unwrap();
+    assert!(last.is_err());
+    assert!(init.iter().all(|x| x.is_ok()));
+}
+
+#[test]
+#[should_panic]
+fn checked_iterate_panic() {
+    let mut teo = adaptor::checked(explicit::Euler::new(BlowUp, 0.1));
+    let mut x = arr1(&[1.0]);
+    teo.iterate_n(&mut x, 1000);
+}
@@ -0,0 +1,128 @@
+//! Models and helpers shared by the integration tests
+
+#![allow(dead_code)]
+
+use ndarray::*;
+
+use eom::*;
+
+/// $dx/dt = -x$ with `n` components
+#[derive(Clone, Copy)]
+pub struct Decay(pub usize);
+
+impl ModelSpec for Decay {
+    type Scalar = f64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        self.0
+    }
+}
+
+impl Explicit for Decay {
+    fn rhs<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        x.mapv_inplace(|x| -x);
+        x
+    }
+}
+
+/// $dx/dt = -x + \cos t$, whose solution is
+/// $x(t) = (x_0 - 1/2) e^{-t} + (\cos t + \sin t) / 2$
+#[derive(Clone, Copy)]
+pub struct Forced;
+
+impl Forced {
+    /// Solution at `t` starting from `x0` at `t = 0`
+    pub fn solution(x0: f64, t: f64) -> f64 {
+        (x0 - 0.5) * (-t).exp() + 0.5 * (t.cos() + t.sin())
+    }
+}
+
+impl ModelSpec for Forced {
+    type Scalar = f64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        1
+    }
+}
+
+impl NonAutonomousExplicit for Forced {
+    fn rhs_t<'a, S>(&mut self, t: f64, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        x.mapv_inplace(|x| -x + t.cos());
+        x
+    }
+}
+
+impl NonAutonomousSemiImplicit for Forced {
+    fn nlin_t<'a, S>(&mut self, t: f64, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        x.fill(t.cos());
+        x
+    }
+
+    fn lin_diag(&self) -> Array1<f64> {
+        arr1(&[-1.0])
+    }
+}
+
+/// Right hand side is NaN everywhere, which no step size can resolve
+#[derive(Clone, Copy)]
+pub struct Singular;
+
+impl ModelSpec for Singular {
+    type Scalar = f64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        2
+    }
+}
+
+impl Explicit for Singular {
+    fn rhs<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        x.fill(f64::NAN);
+        x
+    }
+}
+
+/// Harmonic oscillator $dx/dt = y$, $dy/dt = -x$, i.e. $x = \cos t$, $y = -\sin t$ from $(1, 0)$
+#[derive(Clone, Copy)]
+pub struct Oscillator;
+
+impl ModelSpec for Oscillator {
+    type Scalar = f64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        2
+    }
+}
+
+impl Explicit for Oscillator {
+    fn rhs<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        let (p, q) = (x[0], x[1]);
+        x[0] = q;
+        x[1] = -p;
+        x
+    }
+}
+
+/// Observed order of `teo` from the errors of two successive halvings of `dt`
+pub fn order<Sc>(teo: Sc, x0: &Array1<f64>, dt: f64, steps: usize) -> f64
+where
+    Sc: Scheme<Scalar = f64, Dim = Ix1, Time = f64> + CurrentTime,
+{
+    let acc = adaptor::accuracy(teo, x0.clone(), dt, steps, 3);
+    (acc[0].1 / acc[1].1).log2()
+}
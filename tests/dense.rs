@@ -0,0 +1,116 @@
+use ndarray::*;
+use ndarray_linalg::*;
+
+use eom::*;
+
+/// $dx/dt = -x$
+#[derive(Clone, Copy)]
+struct Decay;
+
+impl ModelSpec for Decay {
+    type Scalar = f64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        2
+    }
+}
+
+impl Explicit for Decay {
+    fn rhs<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        x.mapv_inplace(|x| -x);
+        x
+    }
+}
+
+/// Maximum error of the dense output at the midpoints of `n` steps
+fn midpoint_error<TEO>(mut teo: TEO, n: usize) -> f64
+where
+    TEO: DenseOutput<Scalar = f64, Dim = Ix1, Time = f64>,
+{
+    teo.set_dense_output(true);
+    let x0 = arr1(&[1.0, 2.0]);
+    let mut x = x0.clone();
+    let mut mid = Array::zeros(2);
+    let mut err: f64 = 0.0;
+    for _ in 0..n {
+        teo.iterate(&mut x);
+        teo.dense_output(0.5, &mut mid);
+        let t = teo.get_time() - 0.5 * teo.last_dt();
+        let ans = &x0 * (-t).exp();
+        err = err.max((&mid - &ans).norm_max());
+    }
+    err
+}
+
+#[test]
+fn dense_hermite() {
+    // Hermite interpolation does not reduce the order of the schemes
+    for (name, order) in [("euler", 1.0), ("heun", 2.0), ("rk4", 3.9)] {
+        let err = |dt: f64| {
+            let n = (1.0 / dt) as usize;
+            match name {
+                "euler" => midpoint_error(explicit::Euler::new(Decay, dt), n),
+                "heun" => midpoint_error(explicit::Heun::new(Decay, dt), n),
+                _ => midpoint_error(explicit::RK4::new(Decay, dt), n),
+            }
+        };
+        let rate = (err(0.02) / err(0.01)).log2();
+        assert!(rate > order - 0.2, "{}: rate = {}", name, rate);
+    }
+}
+
+#[test]
+fn dense_dp45() {
+    let mut teo = explicit::DormandPrince45::new(Decay, 0.1);
+    teo.set_tolerance(1e-10, 1e-10);
+    let err = midpoint_error(teo, 100);
+    assert!(err < 1e-8, "err = {}", err);
+}
+
+#[test]
+#[should_panic]
+fn dense_disabled() {
+    let mut teo = explicit::RK4::new(Decay, 0.1);
+    let mut x = arr1(&[1.0, 2.0]);
+    teo.iterate(&mut x);
+    teo.dense_output(0.5, &mut x);
+}
+
+#[test]
+fn last_dt_without_dense() {
+    let mut x = arr1(&[1.0, 2.0]);
+    let mut teo = explicit::Euler::new(Decay, 0.1);
+    teo.iterate(&mut x);
+    assert_eq!(teo.last_dt(), 0.1);
+    let mut teo = explicit::Heun::new(Decay, 0.1);
+    teo.iterate(&mut x);
+    teo.set_dt(0.05);
+    assert_eq!(teo.last_dt(), 0.1);
+    let mut teo = explicit::RK4::new(Decay, 0.1);
+    teo.iterate(&mut x);
+    teo.set_dt(0.05);
+    teo.iterate(&mut x);
+    assert_eq!(teo.last_dt(), 0.05);
+}
+
+#[test]
+fn sample() {
+    let times: Vec<f64> = vec![0.0, 0.0, 0.123, 0.5, 0.777, 1.0, 2.345];
+    let x0 = arr1(&[1.0, 2.0]);
+
+    let mut teo = explicit::RK4::new(Decay, 0.01);
+    let xs: Vec<_> = adaptor::sample(x0.clone(), &mut teo, times.iter().cloned()).collect();
+    assert_eq!(xs.len(), times.len());
+    for (t, x) in times.iter().zip(xs.iter()) {
+        close_l2(x, &(&x0 * (-t).exp()), 1e-9);
+    }
+
+    let mut teo = explicit::DOP853::new(Decay, 0.1);
+    let xs: Vec<_> = adaptor::sample(x0.clone(), &mut teo, times.iter().cloned()).collect();
+    for (t, x) in times.iter().zip(xs.iter()) {
+        close_l2(x, &(&x0 * (-t).exp()), 1e-8);
+    }
+}
@@ -0,0 +1,74 @@
+use ndarray::*;
+use ndarray_linalg::*;
+
+use eom::*;
+
+mod common;
+use common::*;
+
+#[test]
+fn etd_order() {
+    let l63 = ode::Lorenz63::default();
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    let o = order(semi_implicit::ETD1::new(l63, 1.0), &x0, 0.001, 1000);
+    assert!((o - 1.0).abs() < 0.3, "ETD1: {}", o);
+    let o = order(semi_implicit::ETDRK2::new(l63, 1.0), &x0, 0.001, 1000);
+    assert!((o - 2.0).abs() < 0.3, "ETDRK2: {}", o);
+    let o = order(semi_implicit::ETDRK4::new(l63, 1.0), &x0, 0.001, 1000);
+    assert!((o - 4.0).abs() < 0.3, "ETDRK4: {}", o);
+}
+
+/// $dx/dt = \lambda x + c$ with complex $\lambda$
+#[derive(Clone)]
+struct Linear {
+    lambda: Array1<c64>,
+    c: c64,
+}
+
+impl ModelSpec for Linear {
+    type Scalar = c64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        self.lambda.len()
+    }
+}
+
+impl SemiImplicit for Linear {
+    fn nlin<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = c64>,
+    {
+        x.fill(self.c);
+        x
+    }
+
+    fn diag(&self) -> Array1<c64> {
+        self.lambda.clone()
+    }
+}
+
+#[test]
+fn etd_complex() {
+    let c = c64::new(1.0, 0.5);
+    let lambda = arr1(&[
+        c64::new(-1.0, 2.0),
+        c64::new(-100.0, 0.0),
+        c64::new(0.0, 10.0),
+        c64::new(-1e-3, 0.0),
+    ]);
+    let eom = Linear {
+        lambda: lambda.clone(),
+        c,
+    };
+    let x0 = Array1::from_elem(4, c64::new(1.0, 0.0));
+    let t = 1.0;
+    let ans = Zip::from(&lambda)
+        .and(&x0)
+        .map_collect(|&l, &x| (l * t).exp() * (x + c / l) - c / l);
+    let mut teo = semi_implicit::ETD1::new(eom.clone(), 0.1);
+    let x = adaptor::iterate(&mut teo, x0.clone(), 10);
+    close_l2(&x, &ans, 1e-10);
+    let mut teo = semi_implicit::ETDRK4::new(eom, 0.1);
+    let x = adaptor::iterate(&mut teo, x0, 10);
+    close_l2(&x, &ans, 1e-10);
+}
@@ -0,0 +1,83 @@
+use ndarray::*;
+use std::f64::consts::PI;
+
+use eom::{event::*, *};
+
+mod common;
+use common::*;
+
+#[test]
+fn event_times() {
+    let teo = explicit::RK4::new(Oscillator, 0.05);
+    let mut detector = Detector::new(teo);
+    detector.add_event(Event::new(|_t, x| x[0]));
+    let mut increasing = Event::new(|_t, x| x[0]);
+    increasing.set_direction(Direction::Increasing);
+    detector.add_event(increasing);
+
+    let traj = detector.run(arr1(&[1.0, 0.0]), 200);
+    assert!(!traj.terminated);
+    assert_eq!(traj.x.len(), 201);
+    assert!((traj.t[200] - 10.0).abs() < 1e-10);
+
+    // x = cos t vanishes at t = pi/2 + k pi
+    let both: Vec<_> = traj.events.iter().filter(|r| r.event == 0).collect();
+    assert_eq!(both.len(), 3);
+    for (k, root) in both.iter().enumerate() {
+        let t = PI / 2.0 + k as f64 * PI;
+        assert!((root.t - t).abs() < 1e-6, "{} != {}", root.t, t);
+        assert!(root.x[0].abs() < 1e-6);
+    }
+    // increasing crossing only at t = 3pi/2
+    let inc: Vec<_> = traj.events.iter().filter(|r| r.event == 1).collect();
+    assert_eq!(inc.len(), 1);
+    assert!((inc[0].t - 1.5 * PI).abs() < 1e-6);
+    // events are in time order
+    assert!(traj.events.windows(2).all(|w| w[0].t <= w[1].t));
+}
+
+#[test]
+fn terminal_event() {
+    let teo = explicit::RK4::new(ode::Roessler::default(), 0.01);
+    let mut detector = Detector::new(teo);
+    let mut event = Event::new(|_t, x| x[0]);
+    event.set_direction(Direction::Decreasing);
+    event.set_terminal(true);
+    detector.add_event(event);
+
+    let traj = detector.run(arr1(&[1.0, 0.0, 0.0]), 100_000);
+    assert!(traj.terminated);
+    assert_eq!(traj.events.len(), 1);
+    let root = &traj.events[0];
+    assert!(root.x[0].abs() < 1e-8);
+    // trajectory ends at the event
+    assert_eq!(*traj.t.last().unwrap(), root.t);
+    assert_eq!(traj.x.last().unwrap(), &root.x);
+    assert_eq!(detector.teo().get_time(), root.t);
+}
+
+#[test]
+fn restart_after_terminal_event() {
+    let teo = explicit::RK4::new(Oscillator, 0.05);
+    let mut detector = Detector::new(teo);
+    // the secant of a step is used as it is, which stops before the root since x = cos t is concave
+    detector.set_tolerance(0.1);
+    let mut event = Event::new(|_t, x| x[0] - 0.5);
+    event.set_terminal(true);
+    detector.add_event(event);
+
+    let first = detector.run(arr1(&[1.0, 0.0]), 1000);
+    assert!(first.terminated);
+    assert!(first.events[0].x[0] > 0.5);
+    assert!((first.events[0].t - PI / 3.0).abs() < 1e-2);
+
+    // resume from the event without firing it again at once
+    let x = first.x.last().unwrap().clone();
+    let second = detector.run(x, 1000);
+    assert!(second.terminated);
+    assert!(
+        (second.events[0].t - 5.0 * PI / 3.0).abs() < 1e-2,
+        "{}",
+        second.events[0].t
+    );
+}
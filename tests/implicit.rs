@@ -0,0 +1,105 @@
+use ndarray::*;
+use ndarray_linalg::*;
+
+use eom::*;
+
+mod common;
+use common::*;
+
+/// Robertson's chemical kinetics, a standard stiff problem
+#[derive(Clone, Copy)]
+struct Robertson;
+
+impl ModelSpec for Robertson {
+    type Scalar = f64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        3
+    }
+}
+
+impl Explicit for Robertson {
+    fn rhs<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        let (a, b, c) = (x[0], x[1], x[2]);
+        x[0] = -0.04 * a + 1e4 * b * c;
+        x[1] = 0.04 * a - 1e4 * b * c - 3e7 * b * b;
+        x[2] = 3e7 * b * b;
+        x
+    }
+}
+
+fn robertson_jacobian(_: &mut Robertson, _t: f64, x: &Array1<f64>) -> Array2<f64> {
+    let (b, c) = (x[1], x[2]);
+    arr2(&[
+        [-0.04, 1e4 * c, 1e4 * b],
+        [0.04, -1e4 * c - 6e7 * b, -1e4 * b],
+        [0.0, 6e7 * b, 0.0],
+    ])
+}
+
+fn robertson(mut teo: implicit::BDF<Robertson>) -> usize {
+    teo.set_tolerance(1e-10, 1e-6);
+    let mut x = arr1(&[1.0, 0.0, 0.0]);
+    let mut steps = 0;
+    while teo.get_time() < 40.0 {
+        if teo.get_time() + teo.get_dt() > 40.0 {
+            let dt = 40.0 - teo.get_time();
+            teo.set_dt(dt);
+        }
+        teo.iterate(&mut x);
+        steps += 1;
+    }
+    // reference solution at t = 40
+    let ans = arr1(&[0.7158270687, 9.185534764e-6, 0.2841637457]);
+    close_l2(&x, &ans, 1e-4);
+    assert!((x.sum() - 1.0).abs() < 1e-8);
+    steps
+}
+
+#[test]
+fn bdf_robertson() {
+    let teo = implicit::BDF::new(Robertson, 1e-6);
+    let steps = robertson(teo);
+    // explicit schemes require ~1e6 steps due to the stiffness
+    assert!(steps < 1000);
+}
+
+#[test]
+fn bdf_robertson_jacobian() {
+    let mut teo = implicit::BDF::new(Robertson, 1e-6);
+    teo.set_jacobian(robertson_jacobian);
+    robertson(teo);
+}
+
+#[test]
+fn bdf_order() {
+    let mut teo = implicit::BDF::new(Forced, 0.01);
+    teo.set_tolerance(1e-10, 1e-10);
+    let mut x = arr1(&[1.0]);
+    let mut max_order = 1;
+    while teo.get_time() < 3.0 {
+        teo.iterate(&mut x);
+        max_order = max_order.max(teo.order());
+    }
+    let t = teo.get_time();
+    let ans = Forced::solution(1.0, t);
+    assert!((x[0] - ans).abs() < 1e-7);
+    // higher order formulas should be selected for a tight tolerance
+    assert!(max_order >= 3);
+}
+
+#[test]
+fn bdf_underflow() {
+    let mut teo = implicit::BDF::new(Singular, 0.1);
+    let mut x = arr1(&[1.0, 2.0]);
+    assert!(matches!(
+        teo.try_iterate(&mut x),
+        Err(eom::Error::StepUnderflow)
+    ));
+    assert_eq!(x, arr1(&[1.0, 2.0]));
+    assert_eq!(teo.get_time(), 0.0);
+    assert_eq!(teo.get_dt(), 0.1);
+}
@@ -4,6 +4,9 @@ use ndarray_linalg::*;
 use eom::lyapunov::*;
 use eom::*;
 
+mod common;
+use common::*;
+
 #[test]
 fn jacobian_linearity() {
     let eom = ode::Lorenz63::default();
@@ -14,7 +17,7 @@ fn jacobian_linearity() {
     let w: Array1<f64> = generate::random(3);
     let j_z = j.apply(&v + &w);
     let jv_jw = j.apply(v) + j.apply(w);
-    assert_close_l2!(&jv_jw, &j_z, 1e-5);
+    close_l2(&jv_jw, &j_z, 1e-5);
 }
 
 #[test]
@@ -36,3 +39,354 @@ fn jacobian_2d() {
     let v: Array2<f64> = generate::random((3, 2));
     j.apply_multi(v);
 }
+
+#[test]
+fn jacobian_non_autonomous() {
+    // a step of dx/dt = -x + cos t multiplies the perturbation by exp(-dt)
+    let dt = 0.01;
+    let mut teo = explicit::RK4::new(Forced, dt);
+    teo.set_time(1.0);
+    let mut j = teo.lin_approx(arr1(&[1.0]), 1e-7);
+    for _ in 0..3 {
+        let v = j.apply(arr1(&[1.0]));
+        assert!((v[0] - (-dt).exp()).abs() < 1e-6, "{}", v[0]);
+    }
+    // the probes do not advance the time
+    assert!((teo.get_time() - 1.0 - dt).abs() < 1e-12);
+
+    let l = exponents(explicit::RK4::new(Forced, dt), arr1(&[1.0]), 1e-7, 1, 1000);
+    assert!((l[0] + 1.0).abs() < 1e-4, "{}", l[0]);
+}
+
+/// Compare the analytic Jacobian with the finite difference of `rhs`
+fn check_jacobian<F>(mut f: F, x: Array1<f64>)
+where
+    F: AnalyticJacobian<Scalar = f64, Dim = Ix1>,
+{
+    let eps = 1e-7;
+    let dx: Array1<f64> = generate::random(x.len());
+    let mut fx = x.clone();
+    f.rhs(&mut fx);
+    let mut fx_dx = &x + &(eps * &dx);
+    f.rhs(&mut fx_dx);
+    let fd = (fx_dx - fx) / eps;
+    let mut jdx = dx.clone();
+    f.jacobian(&x, &mut jdx);
+    close_l2(&jdx, &fd, 1e-5);
+}
+
+#[test]
+fn analytic_jacobian() {
+    check_jacobian(ode::Lorenz63::default(), arr1(&[1.0, 2.0, 3.0]));
+    check_jacobian(ode::Roessler::default(), arr1(&[1.0, 2.0, 3.0]));
+    check_jacobian(ode::Lorenz96::default(), generate::random(40));
+}
+
+#[test]
+fn tangent_rk4() {
+    let eom = ode::Lorenz63::default();
+    let mut teo = explicit::RK4::new(eom, 0.01);
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    let v: Array2<f64> = generate::random((3, 2));
+    let fd = teo.lin_approx(x0.clone(), 1e-7).apply_multi(v.clone());
+    let mut x = x0.clone();
+    let mut tv = v;
+    teo.iterate_tangent(&mut x, &mut tv);
+    close_l2(&tv, &fd, 1e-5);
+    let mut ans = x0;
+    teo.iterate(&mut ans);
+    close_l2(&x, &ans, 1e-12);
+}
+
+#[test]
+fn tangent_dense() {
+    // dense output follows the tangent step as the plain one
+    let eom = ode::Lorenz63::default();
+    let mut teo = explicit::RK4::new(eom, 0.01);
+    teo.set_dense_output(true);
+    let mut plain = teo.clone();
+    let mut x = arr1(&[1.0, 0.0, 0.0]);
+    let mut y = x.clone();
+    let mut tv: Array2<f64> = Array::eye(3);
+    for _ in 0..2 {
+        teo.iterate_tangent(&mut x, &mut tv);
+        plain.iterate(&mut y);
+        close_l2(&x, &y, 1e-12);
+        let mut a = Array::zeros(3);
+        let mut b = Array::zeros(3);
+        teo.dense_output(0.5, &mut a);
+        plain.dense_output(0.5, &mut b);
+        close_l2(&a, &b, 1e-12);
+    }
+}
+
+#[test]
+fn tangent_exponents() {
+    let eom = ode::Lorenz63::default();
+    let teo = explicit::RK4::new(eom, 0.01);
+    let l = lyapunov::tangent_exponents(teo, arr1(&[1.0, 0.0, 0.0]), 3, 100000);
+    // sum of exponents equals to the phase-space contraction rate -(p + 1 + b)
+    let sum = -(10.0 + 1.0 + 8.0 / 3.0);
+    assert!((l.sum() - sum).abs() < 1e-3);
+    assert!((l[0] - 0.9056).abs() < 0.05);
+    assert!(l[1].abs() < 0.05);
+}
+
+#[test]
+fn covariant_vectors() {
+    let eom = ode::Lorenz63::default();
+    let teo = explicit::RK4::new(eom, 0.01);
+    let mut tangent = teo.clone();
+    let series = Series::tangent(teo, arr1(&[1.0, 0.0, 0.0]), 3);
+    let clv: Vec<_> = CovariantVectors::new(series, 1000, 3000, 100)
+        .take(250)
+        .collect();
+    for w in clv.windows(2) {
+        let (x, v, f) = &w[0];
+        let (x_next, v_next, _) = &w[1];
+        let mut x = x.clone();
+        let mut mv = v.clone();
+        tangent.iterate_tangent(&mut x, &mut mv);
+        close_l2(&x, x_next, 1e-12);
+        // CLVs are mapped to themselves by the tangent-linear operator
+        for i in 0..3 {
+            let mv = mv.column(i);
+            let v_next = v_next.column(i);
+            assert!((mv.norm_l2() - f[i]).abs() < 1e-6 * f[i]);
+            assert!((mv.dot(&v_next).abs() / f[i] - 1.0).abs() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn vectors_streaming() {
+    // `vectors` gives the same CLVs at the same step as `CovariantVectors`
+    let eom = ode::Lorenz63::default();
+    let teo = explicit::RK4::new(eom, 0.01);
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    let clv = lyapunov::vectors(teo.clone(), x0.clone(), 1e-7, 3, 200);
+    assert_eq!(clv.len(), 200);
+    let series = Series::new(teo, x0, 1e-7, 3);
+    let streaming: Vec<_> = CovariantVectors::new(series, 20, 20, 200)
+        .take(200)
+        .collect();
+    for ((x, v, f), (xs, vs, fs)) in clv.iter().zip(streaming.iter()) {
+        close_l2(x, xs, 1e-12);
+        close_l2(v, vs, 1e-12);
+        close_l2(f, fs, 1e-12);
+    }
+}
+
+/// $dz/dt = \bar{z}$, which is not holomorphic and has exponents $\pm 1$
+#[derive(Clone, Copy)]
+struct Conjugate;
+
+impl ModelSpec for Conjugate {
+    type Scalar = c64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        1
+    }
+}
+
+impl Explicit for Conjugate {
+    fn rhs<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = c64>,
+    {
+        x.mapv_inplace(|x| x.conj());
+        x
+    }
+}
+
+#[test]
+fn complex_exponents() {
+    let teo = explicit::RK4::new(Conjugate, 0.01);
+    // finite difference of a linear model is exact, and large `alpha` avoids cancellation
+    let l = lyapunov::exponents(teo, arr1(&[c64::new(1.0, 1.0)]), 1e-3, 2, 1000);
+    close_l2(&l, &arr1(&[1.0, -1.0]), 1e-6);
+}
+
+/// $dx_{ij}/dt = \lambda_{ij} x_{ij}$ on a 2D model space
+#[derive(Clone)]
+struct Linear2D {
+    lambda: Array2<f64>,
+}
+
+impl ModelSpec for Linear2D {
+    type Scalar = f64;
+    type Dim = Ix2;
+    fn model_size(&self) -> (usize, usize) {
+        self.lambda.dim()
+    }
+}
+
+impl Explicit for Linear2D {
+    fn rhs<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix2>) -> &'a mut ArrayBase<S, Ix2>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        *x *= &self.lambda;
+        x
+    }
+}
+
+impl AnalyticJacobian for Linear2D {
+    fn jacobian<'a, S, Sx>(
+        &mut self,
+        _x: &ArrayBase<Sx, Ix2>,
+        dx: &'a mut ArrayBase<S, Ix2>,
+    ) -> &'a mut ArrayBase<S, Ix2>
+    where
+        S: DataMut<Elem = f64>,
+        Sx: Data<Elem = f64>,
+    {
+        self.rhs(dx)
+    }
+}
+
+#[test]
+fn multi_dimensional_exponents() {
+    let eom = Linear2D {
+        lambda: arr2(&[[-0.2, 0.5], [-1.0, 0.1]]),
+    };
+    // tangent vectors are not mixed, and thus exponents are in the order of flattened elements
+    let ans = arr1(&[-0.2, 0.5, -1.0, 0.1]);
+    let x0 = Array2::ones((2, 2));
+    let teo = explicit::RK4::new(eom.clone(), 0.01);
+    let l = lyapunov::exponents(teo, x0.clone(), 1e-3, 4, 1000);
+    close_l2(&l, &ans, 1e-6);
+    let teo = explicit::RK4::new(eom, 0.01);
+    let l = lyapunov::tangent_exponents(teo, x0, 4, 1000);
+    close_l2(&l, &ans, 1e-6);
+}
+
+#[test]
+fn partial_exponents() {
+    let eom = ode::Lorenz96::default();
+    let x0: Array1<f64> = generate::random(40);
+    let teo = explicit::RK4::new(eom, 0.01);
+    let full = lyapunov::tangent_exponents(teo.clone(), x0.clone(), 40, 1000);
+    // Gram-Schmidt process of the leading vectors does not depend on the others
+    let partial = lyapunov::tangent_exponents(teo, x0, 5, 1000);
+    assert_eq!(partial.len(), 5);
+    close_l2(&partial, &full.slice(s![..5]), 1e-8);
+}
+
+#[test]
+fn leading_exponent() {
+    let eom = ode::Lorenz63::default();
+    let teo = explicit::RK4::new(eom, 0.01);
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    let full = lyapunov::exponents(teo.clone(), x0.clone(), 1e-7, 3, 1000);
+    let leading = lyapunov::exponents(teo, x0, 1e-7, 1, 1000);
+    assert_eq!(leading.len(), 1);
+    assert!((leading[0] - full[0]).abs() < 1e-6);
+}
+
+#[test]
+fn finite_time_exponents() {
+    let eom = ode::Lorenz63::default();
+    let teo = explicit::RK4::new(eom, 0.01);
+    let window = 100;
+    let ftle: Vec<_> = Series::tangent(teo, arr1(&[1.0, 0.0, 0.0]), 3)
+        .finite_time(1000, window)
+        .take(10000)
+        .collect();
+    assert!(ftle[window - 2].ftle.is_none());
+    assert!(ftle[2 * window - 2].drift.is_none());
+    let last = ftle.last().unwrap();
+    // FTLE is the mean of local exponents in the window
+    let mean = ftle[ftle.len() - window..]
+        .iter()
+        .fold(Array1::zeros(3), |m, l| m + &l.local)
+        / window as f64;
+    close_l2(last.ftle.as_ref().unwrap(), &mean, 1e-10);
+    // sum of exponents equals to the phase-space contraction rate -(p + 1 + b)
+    let sum = -(10.0 + 1.0 + 8.0 / 3.0);
+    assert!((last.mean.sum() - sum).abs() < 1e-2);
+    assert!((last.mean[0] - 0.9056).abs() < 0.1);
+    assert!(last.drift.unwrap() < 0.05);
+}
+
+#[test]
+fn spectrum_statistics() {
+    // Lorenz 63 model
+    let l = arr1(&[0.0, -14.5723, 0.9056]);
+    let d = lyapunov::kaplan_yorke_dimension(&l);
+    assert!((d - (2.0 + 0.9056 / 14.5723)).abs() < 1e-12);
+    assert_eq!(lyapunov::ks_entropy(&l), 0.9056);
+    assert_eq!(
+        lyapunov::sign_count(&l, 1e-3),
+        lyapunov::SignCount {
+            positive: 1,
+            zero: 1,
+            negative: 1
+        }
+    );
+    // stable fixed point and insufficient partial spectrum
+    assert_eq!(lyapunov::kaplan_yorke_dimension(&arr1(&[-1.0, -2.0])), 0.0);
+    assert_eq!(lyapunov::kaplan_yorke_dimension(&arr1(&[1.0, 0.0])), 2.0);
+    // spectrum of a divergent run
+    assert!(lyapunov::kaplan_yorke_dimension(&arr1(&[1.0, f64::NAN, -2.0])).is_nan());
+    assert!(lyapunov::kaplan_yorke_dimension(&arr1(&[f64::INFINITY, -2.0])).is_nan());
+}
+
+#[test]
+fn converged_exponents() {
+    let eom = ode::Lorenz63::default();
+    let teo = explicit::RK4::new(eom, 0.01);
+    let series = Series::tangent(teo, arr1(&[1.0, 0.0, 0.0]), 3);
+    let result = lyapunov::converged_exponents(series, 1000, 1000, 1e-2, 1_000_000).unwrap();
+    assert!(result.stderr.unwrap().iter().all(|&e| e < 1e-2));
+    assert!(result.steps < 1_000_000);
+    assert!((result.exponents[0] - 0.9056).abs() < 0.05);
+
+    let teo = explicit::RK4::new(eom, 0.01);
+    let series = Series::tangent(teo, arr1(&[1.0, 0.0, 0.0]), 3);
+    let result = lyapunov::converged_exponents(series, 1000, 1000, 1e-8, 3000);
+    assert!(matches!(result, Err(Error::NotConverged { stderr: Some(e) }) if e >= 1e-8));
+}
+
+/// $dx/dt = x^2$, which blows up at $t = 1/x_0$
+#[derive(Clone, Copy)]
+struct BlowUp;
+
+impl ModelSpec for BlowUp {
+    type Scalar = f64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        1
+    }
+}
+
+impl Explicit for BlowUp {
+    fn rhs<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        x.mapv_inplace(|x| x * x);
+        x
+    }
+}
+
+#[test]
+fn try_exponents_non_finite() {
+    let teo = explicit::RK4::new(BlowUp, 0.1);
+    let result = lyapunov::try_exponents(teo, arr1(&[1.0]), 1e-7, 1, 100);
+    assert!(matches!(result, Err(Error::NonFinite)));
+
+    let teo = explicit::RK4::new(BlowUp, 0.1);
+    let result = lyapunov::try_vectors(teo, arr1(&[1.0]), 1e-7, 1, 100);
+    assert!(matches!(result, Err(Error::NonFinite)));
+
+    let teo = explicit::RK4::new(BlowUp, 0.1);
+    let series = Series::new(teo, arr1(&[1.0]), 1e-7, 1);
+    let result = lyapunov::converged_exponents(series, 0, 10, 1e-2, 100);
+    assert!(matches!(result, Err(Error::NonFinite)));
+
+    let teo = explicit::RK4::new(BlowUp, 0.1);
+    let mut series = Series::new(teo, arr1(&[1.0]), 1e-7, 1);
+    assert!(series.try_next().is_ok());
+    assert!((0..100).any(|_| series.try_next().is_err()));
+}
@@ -0,0 +1,48 @@
+use ndarray::*;
+use ndarray_linalg::*;
+
+use eom::*;
+
+mod common;
+use common::*;
+
+fn solution(t: f64) -> Array1<f64> {
+    arr1(&[Forced::solution(1.0, t)])
+}
+
+fn check<TEO>(mut teo: TEO, tol: f64)
+where
+    TEO: TimeEvolution<Scalar = f64, Dim = Ix1> + CurrentTime<Time = f64>,
+{
+    let mut x = arr1(&[1.0]);
+    while teo.get_time() < 3.0 {
+        teo.iterate(&mut x);
+    }
+    close_l2(&x, &solution(teo.get_time()), tol);
+}
+
+#[test]
+fn forced_explicit() {
+    let dt = 0.01;
+    check(explicit::Euler::new(Forced, dt), 1e-2);
+    check(explicit::Heun::new(Forced, dt), 1e-4);
+    check(explicit::RK4::new(Forced, dt), 1e-8);
+    check(explicit::DormandPrince45::new(Forced, dt), 1e-5);
+    check(explicit::DOP853::new(Forced, dt), 1e-8);
+}
+
+#[test]
+fn forced_semi_implicit() {
+    check(semi_implicit::DiagRK4::new(Forced, 0.01), 1e-8);
+}
+
+#[test]
+fn set_time() {
+    let mut teo = explicit::RK4::new(Forced, 0.01);
+    teo.set_time(1.0);
+    let mut x = solution(1.0);
+    for _ in 0..100 {
+        teo.iterate(&mut x);
+    }
+    close_l2(&x, &solution(2.0), 1e-8);
+}
@@ -2,7 +2,7 @@ use ndarray::*;
 use ndarray_linalg::*;
 use std::f64::consts::PI;
 
-use eom::pde::*;
+use eom::{pde::*, *};
 
 #[test]
 fn pair_r2c2r() {
@@ -13,7 +13,13 @@ fn pair_r2c2r() {
     p.r2c();
     p.c2r();
     let b: Array1<f64> = Array::from_iter(p.r.iter().cloned());
-    assert_close_l2!(&a, &b, 1e-7);
+    close_l2(&a, &b, 1e-7);
+}
+
+#[test]
+fn pair_try_new() {
+    let mut p = Pair::try_new(128).unwrap();
+    p.c2r();
 }
 
 #[test]
@@ -25,5 +31,393 @@ fn pair_c2r() {
     p.c[1] = c64::new(1.0, 0.0);
     p.c2r();
     let b = Array::from_iter(p.r.iter().cloned());
-    assert_close_l2!(&a, &b, 1e-7);
+    close_l2(&a, &b, 1e-7);
+}
+
+#[test]
+fn pair2d_r2c2r() {
+    let shape = (16, 12);
+    let a: Array2<f64> = random(shape);
+    let mut p = Pair2D::new(shape);
+    assert_eq!(p.coeff_shape(), Dim([16, 7]));
+    p.real_view_mut().assign(&a);
+    p.r2c();
+    p.c2r();
+    close_l2(&p.real_view(), &a, 1e-7);
+}
+
+#[test]
+fn pair2d_c2r() {
+    let (nx, ny) = (16, 12);
+    let mut p = Pair2D::new((nx, ny));
+    p.coeff_view_mut()[(1, 2)] = c64::new(1.0, 0.0);
+    p.c2r();
+    let a = Array::from_shape_fn((nx, ny), |(i, j)| {
+        2.0 * (2.0 * PI * (i as f64 / nx as f64 + 2.0 * j as f64 / ny as f64)).cos()
+    });
+    close_l2(&p.real_view(), &a, 1e-7);
+}
+
+#[test]
+fn wavenumbers_derivative() {
+    let (nx, ny) = (16, 12);
+    let (lx, ly) = (2.0, 3.0);
+    let k = Wavenumbers2D::new((nx, ny), (lx, ly));
+    assert_eq!(k.dim(), (16, 7));
+    assert_eq!(
+        wavenumbers(5, 2.0 * PI).to_vec(),
+        vec![0.0, 1.0, 2.0, -2.0, -1.0]
+    );
+
+    let x = |i: usize| lx * i as f64 / nx as f64;
+    let y = |j: usize| ly * j as f64 / ny as f64;
+    let (ax, ay) = (2.0 * PI / lx, 4.0 * PI / ly);
+    let u = Array::from_shape_fn((nx, ny), |(i, j)| (ax * x(i)).sin() * (ay * y(j)).cos());
+    let mut p = Pair2D::new((nx, ny));
+    let uf = p.to_c(u.as_slice().unwrap()).to_owned();
+
+    // d/dx
+    let dx = &uf * &k.kx.mapv(|k| c64::new(0.0, k));
+    let dx = p.to_r(dx.as_slice().unwrap()).to_owned();
+    let ans = Array::from_shape_fn((nx, ny), |(i, j)| {
+        ax * (ax * x(i)).cos() * (ay * y(j)).cos()
+    });
+    close_l2(&dx, &ans, 1e-7);
+
+    // Laplacian
+    let lap = &uf * &k.k2.mapv(|k2| c64::new(-k2, 0.0));
+    let lap = p.to_r(lap.as_slice().unwrap()).to_owned();
+    close_l2(&lap, &(-(ax * ax + ay * ay) * &u), 1e-7);
+}
+
+/// Vorticity field in Fourier space
+fn ns_to_c(shape: (usize, usize), f: impl Fn(f64, f64) -> f64) -> Array2<c64> {
+    let (nx, ny) = shape;
+    let w = Array::from_shape_fn(shape, |(i, j)| {
+        f(
+            2.0 * PI * i as f64 / nx as f64,
+            2.0 * PI * j as f64 / ny as f64,
+        )
+    });
+    let mut p = Pair2D::new(shape);
+    p.to_c(w.as_slice().unwrap()).to_owned()
+}
+
+#[test]
+fn navier_stokes_diagnostics() {
+    let shape = (16, 16);
+    let eom = NavierStokes2D::new(shape, (2.0 * PI, 2.0 * PI), 0.01);
+    assert_eq!(eom.model_size(), (16, 9));
+    // psi = cos(x), (u, v) = (0, sin(x))
+    let w = ns_to_c(shape, |x, _y| x.cos());
+    assert!((eom.energy(&w) - 0.25).abs() < 1e-12);
+    assert!((eom.enstrophy(&w) - 0.25).abs() < 1e-12);
+    // psi = cos(x + 2y) / 5
+    let w = ns_to_c(shape, |x, y| (x + 2.0 * y).cos());
+    assert!((eom.energy(&w) - 0.25 / 5.0).abs() < 1e-12);
+    assert!((eom.enstrophy(&w) - 0.25).abs() < 1e-12);
+}
+
+#[test]
+fn navier_stokes_taylor_green() {
+    // Taylor-Green vortex is a steady solution of the Euler equation, and decays by the viscosity
+    let shape = (16, 16);
+    let nu = 0.1;
+    let eom = NavierStokes2D::new(shape, (2.0 * PI, 2.0 * PI), nu);
+    let w0 = ns_to_c(shape, |x, y| 2.0 * x.cos() * y.cos());
+    let e0 = eom.energy(&w0);
+    let mut teo = semi_implicit::DiagRK4::new(eom.clone(), 0.01);
+    let w = adaptor::iterate(&mut teo, w0.clone(), 100);
+    // |k|^2 = 2 and t = 1, vorticity decays as exp(-nu |k|^2 t)
+    let decay = (-2.0 * nu).exp();
+    close_l2(&w, &(&w0 * c64::new(decay, 0.0)), 1e-7);
+    assert!((eom.energy(&w) - e0 * decay * decay).abs() < 1e-9);
+}
+
+#[test]
+fn navier_stokes_kolmogorov() {
+    // laminar Kolmogorov flow w = -F kf cos(kf y) / (nu kf^2 + mu) is stable for a small Reynolds number
+    let shape = (16, 16);
+    let (nu, mu, f, nf) = (0.5, 0.1, 1.0, 2);
+    let mut eom = NavierStokes2D::new(shape, (2.0 * PI, 2.0 * PI), nu);
+    eom.set_drag(mu);
+    eom.set_hyperviscosity(1e-6, 4);
+    eom.set_kolmogorov_forcing(f, nf);
+    let kf = nf as f64;
+    let coef = -f * kf / (nu * kf * kf + mu + 1e-6 * kf.powi(8));
+    let ans = ns_to_c(shape, |_x, y| coef * (kf * y).cos());
+
+    let w0 = ns_to_c(shape, |x, y| 0.1 * (x + y).sin());
+    let mut teo = semi_implicit::DiagRK4::new(eom, 0.05);
+    let w = adaptor::iterate(&mut teo, w0, 1000);
+    close_l2(&w, &ans, 1e-6);
+}
+
+#[test]
+fn dealiasing_filter() {
+    assert_eq!(Dealiasing::default(), Dealiasing::None);
+    assert_eq!(Dealiasing::Padding.grid_size(128), 192);
+    assert_eq!(Dealiasing::TwoThirds.grid_size(128), 128);
+    assert_eq!(Dealiasing::Padding.grid_size_for(128, 3), 256);
+    assert_eq!(Dealiasing::Padding.input_size(128), 64);
+    assert_eq!(Dealiasing::Padding.input_size(127), 64);
+    assert_eq!(Dealiasing::TwoThirds.input_size(128), 65);
+    let f = Dealiasing::TwoThirds.filter(12);
+    assert_eq!(f.to_vec(), vec![1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0]);
+    let f = Dealiasing::Padding.filter(12);
+    assert_eq!(f.to_vec(), vec![1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0]);
+    let f = Dealiasing::hou_li().filter(128);
+    assert_eq!(f[0], 1.0);
+    assert!((f[64] - (-36.0_f64).exp()).abs() < 1e-20);
+    assert!(f.iter().zip(f.iter().skip(1)).all(|(a, b)| a >= b));
+}
+
+/// Coefficients of $\cos(k x)$ on the `n`-point grid
+fn cos_coef(n: usize, k: usize) -> Array1<c64> {
+    let mut c = Array::zeros(n / 2 + 1);
+    c[k] = c64::new(0.5, 0.0);
+    c
+}
+
+#[test]
+fn kse_dealiasing() {
+    // u u_x for u = cos(20 x) has the mode 40, which aliases into the mode 24 on the 64-point grid
+    let n = 64;
+    let u = cos_coef(n, 20);
+    let mut eom = KSE::new(n, 2.0 * PI);
+    let mut f = u.clone();
+    eom.nlin(&mut f);
+    assert!(f[24].norm() > 1e-3);
+    for dealiasing in [Dealiasing::TwoThirds, Dealiasing::Padding] {
+        let mut eom = KSE::with_dealiasing(n, 2.0 * PI, dealiasing);
+        let mut f = u.clone();
+        eom.nlin(&mut f);
+        assert!(f.iter().all(|c| c.norm() < 1e-8), "{:?}", dealiasing);
+    }
+}
+
+#[test]
+fn she_dealiasing() {
+    // u^2 and u^3 for u = cos(20 x) alias into the modes 24 and 4 on the 64-point grid
+    let n = 64;
+    let u = cos_coef(n, 20);
+    let mut eom = SHE::new(n, 2.0 * PI, 1.0, 6.0);
+    let mut f = u.clone();
+    eom.nlin(&mut f);
+    assert!(f[24].norm() > 1e-3);
+    assert!(f[4].norm() > 1e-3);
+
+    // 1.64 u^2 - u^3 = 0.82 + 0.82 cos(40 x) - 0.75 cos(20 x) - 0.25 cos(60 x)
+    let mut ans = Array::zeros(n / 2 + 1);
+    ans[0] = c64::new(0.82, 0.0);
+    ans[20] = c64::new(-0.375, 0.0);
+    let mut eom = SHE::with_dealiasing(n, 2.0 * PI, 1.0, 6.0, Dealiasing::Padding);
+    let mut f = u.clone();
+    eom.nlin(&mut f);
+    close_l2(&f, &ans, 1e-8);
+
+    // u^3 for u = cos(24 x) has the mode 72, which aliases into the mode 24 on the 3/2 padded grid
+    let u = cos_coef(n, 24);
+    let mut ans = Array::zeros(n / 2 + 1);
+    ans[0] = c64::new(0.82, 0.0);
+    ans[24] = c64::new(-0.375, 0.0);
+    let mut f = u.clone();
+    eom.nlin(&mut f);
+    close_l2(&f, &ans, 1e-8);
+}
+
+#[test]
+fn padding_nyquist() {
+    // the Nyquist mode does not contribute to the nonlinear terms
+    let n = 64;
+    let mut u = Array::zeros(n / 2 + 1);
+    u[n / 2] = c64::new(1.0, 0.0);
+    let mut eom = SHE::with_dealiasing(n, 2.0 * PI, 1.0, 6.0, Dealiasing::Padding);
+    let mut f = u.clone();
+    eom.nlin(&mut f);
+    assert!(f.iter().all(|c| c.norm() < 1e-8), "{:?}", f);
+    let mut eom = KSE::with_dealiasing(n, 2.0 * PI, Dealiasing::Padding);
+    let mut f = u.clone();
+    eom.nlin(&mut f);
+    assert!(f.iter().all(|c| c.norm() < 1e-8), "{:?}", f);
+}
+
+#[test]
+fn cpair_forward_backward() {
+    let a: Array1<c64> = random(12);
+    let mut p = CPair::new(12);
+    p.field_view_mut().assign(&a);
+    p.forward();
+    p.backward();
+    close_l2(&p.field_view(), &a, 1e-7);
+
+    let a: Array2<c64> = random((8, 6));
+    let mut p = CPair2D::new((8, 6));
+    let c = p.to_c(a.as_slice().unwrap()).to_owned();
+    close_l2(&p.to_field(c.as_slice().unwrap()), &a, 1e-7);
+}
+
+#[test]
+fn cpair_backward() {
+    // coefficients are in the order of FFT, i.e. the index `n - 1` is the wavenumber -1
+    let n = 16;
+    let mut p = CPair::new(n);
+    p.c[n - 1] = c64::new(1.0, 0.0);
+    p.backward();
+    let a = Array::from_shape_fn(n, |i| c64::from_polar(1.0, -2.0 * PI * i as f64 / n as f64));
+    close_l2(&p.field_view(), &a, 1e-7);
+}
+
+/// Plane wave solution of CGLE with the amplitude $\sqrt{1 - q^2}$ at `t`
+fn cgle_plane_wave(q2: f64, alpha: f64, beta: f64, t: f64) -> c64 {
+    let omega = alpha * q2 + beta * (1.0 - q2);
+    c64::from_polar((1.0 - q2).sqrt(), -omega * t)
+}
+
+#[test]
+fn cgle_plane_wave_1d() {
+    let (alpha, beta) = (1.5, -1.2);
+    let n = 32;
+    let eom = CGLE::new(n, 8.0 * PI, alpha, beta);
+    assert_eq!(eom.model_size(), n);
+    // q = 2 * 2 pi / 8 pi
+    let q2 = 0.25;
+    let mut a0 = Array::zeros(n);
+    a0[2] = cgle_plane_wave(q2, alpha, beta, 0.0);
+    assert!((eom.norm_sqr(&a0) - 0.75).abs() < 1e-12);
+
+    let mut teo = semi_implicit::DiagRK4::new(eom, 0.01);
+    let a = adaptor::iterate(&mut teo, a0, 100);
+    let mut ans = Array::zeros(n);
+    ans[2] = cgle_plane_wave(q2, alpha, beta, 1.0);
+    close_l2(&a, &ans, 1e-7);
+}
+
+#[test]
+fn cgle_plane_wave_2d() {
+    let (alpha, beta) = (2.0, -0.5);
+    let shape = (16, 12);
+    let eom = CGLE::new_2d(shape, (8.0 * PI, 8.0 * PI), alpha, beta);
+    assert_eq!(eom.model_size(), shape);
+    // q = (1, -1) * 2 pi / 8 pi
+    let q2 = 0.125;
+    let mut a0 = Array::zeros(shape);
+    a0[(1, 11)] = cgle_plane_wave(q2, alpha, beta, 0.0);
+
+    let mut teo = semi_implicit::DiagRK4::new(eom, 0.01);
+    let a = adaptor::iterate(&mut teo, a0, 100);
+    let mut ans = Array::zeros(shape);
+    ans[(1, 11)] = cgle_plane_wave(q2, alpha, beta, 1.0);
+    close_l2(&a, &ans, 1e-7);
+}
+
+/// Fourier coefficients of the real function `f(x)` on `n` points of `[0, length]`
+fn real_to_c(n: usize, length: f64, f: impl Fn(f64) -> f64) -> Array1<c64> {
+    let u = Array::from_shape_fn(n, |i| f(length * i as f64 / n as f64));
+    let mut p = Pair::new(n);
+    Array::from_iter(p.to_c(u.as_slice().unwrap()).iter().cloned())
+}
+
+/// Fourier coefficients of the complex function `f(x)` on `n` points of `[0, length]`
+fn complex_to_c(n: usize, length: f64, f: impl Fn(f64) -> c64) -> Array1<c64> {
+    let u = Array::from_shape_fn(n, |i| f(length * i as f64 / n as f64));
+    let mut p = CPair::new(n);
+    p.to_c(u.as_slice().unwrap()).to_owned()
+}
+
+#[test]
+fn kdv_soliton() {
+    let (n, length) = (192, 60.0);
+    let c = 1.0;
+    let soliton = |x0: f64| move |x: f64| 0.5 * c / (0.5 * c.sqrt() * (x - x0)).cosh().powi(2);
+    let u0 = real_to_c(n, length, soliton(25.0));
+
+    let mut eom = KdV::new(n, length);
+    let mass = eom.mass(&u0);
+    let momentum = eom.momentum(&u0);
+    let energy = eom.energy(&u0);
+    assert!((mass - 2.0 * c.sqrt() / length).abs() < 1e-8);
+    assert!((momentum - c.powf(1.5) / (3.0 * length)).abs() < 1e-8);
+
+    let mut teo = semi_implicit::DiagRK4::new(eom.clone(), 0.01);
+    let u = adaptor::iterate(&mut teo, u0, 500);
+    close_l2(&u, &real_to_c(n, length, soliton(25.0 + 5.0 * c)), 1e-5);
+    assert!((eom.mass(&u) - mass).abs() < 1e-12);
+    assert!((eom.momentum(&u) - momentum).abs() < 1e-8);
+    assert!((eom.energy(&u) - energy).abs() < 1e-8);
+}
+
+#[test]
+fn kdv_energy_dealiasing() {
+    // <u^3> = 0 for u = cos(21 x) + cos(22 x), while 21 + 21 + 22 aliases into the mode 0 on the 64-point grid
+    let n = 64;
+    let u = cos_coef(n, 21) + cos_coef(n, 22);
+    let kinetic = 0.25 * (21.0 * 21.0 + 22.0 * 22.0);
+    for dealiasing in [Dealiasing::None, Dealiasing::TwoThirds, Dealiasing::Padding] {
+        let mut eom = KdV::with_dealiasing(n, 2.0 * PI, dealiasing);
+        let energy = eom.energy(&u);
+        assert!(
+            (energy - kinetic).abs() < 1e-10,
+            "{:?}: {}",
+            dealiasing,
+            energy
+        );
+    }
+}
+
+#[test]
+fn nls_soliton() {
+    let (n, length) = (128, 40.0);
+    let psi0 = complex_to_c(n, length, |x| c64::new(1.0 / (x - 20.0).cosh(), 0.0));
+
+    let mut eom = NLS::focusing(n, length);
+    let mass = eom.mass(&psi0);
+    let energy = eom.energy(&psi0);
+    assert!((mass - 2.0 / length).abs() < 1e-8);
+    assert!(eom.momentum(&psi0).abs() < 1e-12);
+
+    // the phase rotates as exp(i t / 2)
+    let mut teo = semi_implicit::DiagRK4::new(eom.clone(), 0.01);
+    let psi = adaptor::iterate(&mut teo, psi0.clone(), 200);
+    close_l2(&psi, &(&psi0 * c64::from_polar(1.0, 1.0)), 1e-6);
+    assert!((eom.mass(&psi) - mass).abs() < 1e-8);
+    assert!(eom.momentum(&psi).abs() < 1e-8);
+    assert!((eom.energy(&psi) - energy).abs() < 1e-8);
+}
+
+#[test]
+fn nls_plane_wave() {
+    // psi = a exp(i(qx - wt)) with w = q^2 / 2 - kappa a^2
+    let n = 16;
+    let (a, q) = (0.5, 3);
+    for (kappa, mut eom) in [
+        (1.0, NLS::focusing(n, 2.0 * PI)),
+        (-1.0, NLS::defocusing(n, 2.0 * PI)),
+    ] {
+        let mut psi0 = Array::zeros(n);
+        psi0[q] = c64::new(a, 0.0);
+        assert!((eom.momentum(&psi0) - q as f64 * a * a).abs() < 1e-12);
+        let energy = 0.5 * (q * q) as f64 * a * a - 0.5 * kappa * a.powi(4);
+        assert!((eom.energy(&psi0) - energy).abs() < 1e-12);
+
+        let mut teo = semi_implicit::DiagRK4::new(eom, 0.01);
+        let psi = adaptor::iterate(&mut teo, psi0.clone(), 100);
+        let w = 0.5 * (q * q) as f64 - kappa * a * a;
+        close_l2(&psi, &(&psi0 * c64::from_polar(1.0, -w)), 1e-7);
+    }
+}
+
+#[test]
+fn nls_defocusing_conservation() {
+    let (n, length) = (64, 2.0 * PI);
+    let psi0 = complex_to_c(n, length, |x| {
+        c64::new(1.0 + 0.2 * x.cos(), 0.3 * (2.0 * x).sin())
+    });
+    let mut eom = NLS::defocusing(n, length);
+    let (mass, momentum, energy) = (eom.mass(&psi0), eom.momentum(&psi0), eom.energy(&psi0));
+    let mut teo = semi_implicit::DiagRK4::new(eom.clone(), 0.001);
+    let psi = adaptor::iterate(&mut teo, psi0, 1000);
+    assert!((eom.mass(&psi) - mass).abs() < 1e-8);
+    assert!((eom.momentum(&psi) - momentum).abs() < 1e-8);
+    assert!((eom.energy(&psi) - energy).abs() < 1e-8);
 }
@@ -0,0 +1,204 @@
+use ndarray::*;
+use std::f64::consts::PI;
+
+use eom::{event::Direction, *};
+
+mod common;
+use common::*;
+
+#[test]
+fn poincare_oscillator() {
+    let teo = explicit::RK4::new(Oscillator, 0.05);
+    let section = adaptor::poincare(
+        teo,
+        arr1(&[1.0, 0.0]),
+        arr1(&[1.0, 0.0]),
+        0.0,
+        Direction::Increasing,
+    );
+    for (k, p) in section.take(5).enumerate() {
+        // x increases across zero at t = 3pi/2 + 2k pi, where y = 1
+        assert!((p.t - (1.5 + 2.0 * k as f64) * PI).abs() < 1e-5);
+        assert!(p.x[0].abs() < 1e-6);
+        assert!((p.x[1] - 1.0).abs() < 1e-5);
+        match p.return_time {
+            Some(t) => assert!((t - 2.0 * PI).abs() < 1e-5),
+            None => assert_eq!(k, 0),
+        }
+    }
+}
+
+#[test]
+fn poincare_roessler() {
+    let teo = explicit::RK4::new(ode::Roessler::default(), 0.01);
+    let section = adaptor::poincare(
+        teo,
+        arr1(&[1.0, 0.0, 0.0]),
+        arr1(&[1.0, 0.0, 0.0]),
+        0.0,
+        Direction::Decreasing,
+    );
+    for p in section.skip(1).take(100) {
+        assert!(p.x[0].abs() < 1e-8);
+        // x decreases across zero in the half plane y > 0
+        assert!(p.x[1] > 0.0);
+        let t = p.return_time.unwrap();
+        assert!(t > 5.0 && t < 7.0, "return time = {}", t);
+    }
+}
+
+/// Harmonic oscillator $H = (p^2 + q^2) / 2$, i.e. $q = \cos t$, $p = -\sin t$
+#[derive(Clone, Copy)]
+struct Harmonic;
+
+impl ModelSpec for Harmonic {
+    type Scalar = f64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        2
+    }
+}
+
+impl Hamiltonian for Harmonic {
+    fn energy<S>(&mut self, q: &ArrayBase<S, Ix1>, p: &ArrayBase<S, Ix1>) -> f64
+    where
+        S: Data<Elem = f64>,
+    {
+        0.5 * (p[0] * p[0] + q[0] * q[0])
+    }
+
+    fn dh_dq<'a, S, T>(
+        &mut self,
+        q: &'a mut ArrayBase<S, Ix1>,
+        _p: &ArrayBase<T, Ix1>,
+    ) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+        T: Data<Elem = f64>,
+    {
+        q
+    }
+
+    fn dh_dp<'a, S, T>(
+        &mut self,
+        _q: &ArrayBase<T, Ix1>,
+        p: &'a mut ArrayBase<S, Ix1>,
+    ) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+        T: Data<Elem = f64>,
+    {
+        p
+    }
+}
+
+impl SeparableHamiltonian for Harmonic {}
+
+/// Exact rotation of the plane without [CurrentTime]
+#[derive(Clone, Copy)]
+struct Rotation {
+    dt: f64,
+}
+
+impl ModelSpec for Rotation {
+    type Scalar = f64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        2
+    }
+}
+
+impl TimeStep for Rotation {
+    type Time = f64;
+    fn get_dt(&self) -> f64 {
+        self.dt
+    }
+    fn set_dt(&mut self, dt: f64) {
+        self.dt = dt;
+    }
+}
+
+impl TimeEvolution for Rotation {
+    fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        let (c, s) = (self.dt.cos(), self.dt.sin());
+        let (p, q) = (x[0], x[1]);
+        x[0] = c * p + s * q;
+        x[1] = -s * p + c * q;
+        x
+    }
+}
+
+#[test]
+fn poincare_symplectic() {
+    let teo = symplectic::ForestRuth::new(Harmonic, 0.02);
+    let section = adaptor::poincare(
+        teo,
+        arr1(&[1.0, 0.0]),
+        arr1(&[1.0, 0.0]),
+        0.0,
+        Direction::Increasing,
+    );
+    for (k, p) in section.take(5).enumerate() {
+        assert!((p.t - (1.5 + 2.0 * k as f64) * PI).abs() < 1e-5);
+        assert!((p.x[1] - 1.0).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn poincare_without_time() {
+    let teo = Rotation { dt: 0.05 };
+    let section = adaptor::poincare(
+        teo,
+        arr1(&[1.0, 0.0]),
+        arr1(&[1.0, 0.0]),
+        0.0,
+        Direction::Decreasing,
+    );
+    for (k, p) in section.take(5).enumerate() {
+        // x decreases across zero at t = pi/2 + 2k pi, where y = -1
+        assert!((p.t - (0.5 + 2.0 * k as f64) * PI).abs() < 1e-6);
+        assert!((p.x[1] + 1.0).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn poincare_adaptive() {
+    let mut teo = explicit::DormandPrince45::new(Oscillator, 0.1);
+    teo.set_tolerance(1e-10, 1e-10);
+    teo.set_time(1.0);
+    let section = adaptor::poincare(
+        teo,
+        arr1(&[1.0, 0.0]),
+        arr1(&[1.0, 0.0]),
+        0.0,
+        Direction::Increasing,
+    )
+    .current_time();
+    for (k, p) in section.take(5).enumerate() {
+        assert!(
+            (p.t - 1.0 - (1.5 + 2.0 * k as f64) * PI).abs() < 1e-6,
+            "{}",
+            p.t
+        );
+        assert!((p.x[1] - 1.0).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn poincare_max_steps() {
+    let teo = explicit::RK4::new(Oscillator, 0.05);
+    // |x| <= 1 never reaches the section
+    let mut section = adaptor::poincare(
+        teo,
+        arr1(&[1.0, 0.0]),
+        arr1(&[1.0, 0.0]),
+        2.0,
+        Direction::Both,
+    )
+    .max_steps(1000);
+    assert!(section.next().is_none());
+    assert!(section.next().is_none());
+}
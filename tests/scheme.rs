@@ -0,0 +1,121 @@
+use ndarray::*;
+
+use eom::*;
+
+mod common;
+use common::*;
+
+fn dt_round_trip<Sc>(mut teo: Sc)
+where
+    Sc: Scheme<Time = f64>,
+{
+    assert_eq!(teo.get_dt(), 0.1);
+    teo.set_dt(0.025);
+    assert_eq!(teo.get_dt(), 0.025);
+    teo.set_dt(0.3);
+    assert_eq!(teo.get_dt(), 0.3);
+}
+
+#[test]
+fn dt() {
+    let l63 = ode::Lorenz63::default();
+    dt_round_trip(explicit::Euler::new(l63, 0.1));
+    dt_round_trip(explicit::Heun::new(l63, 0.1));
+    dt_round_trip(explicit::RK4::new(l63, 0.1));
+    dt_round_trip(explicit::DormandPrince45::new(l63, 0.1));
+    dt_round_trip(explicit::DOP853::new(l63, 0.1));
+    dt_round_trip(semi_implicit::DiagRK4::new(l63, 0.1));
+    dt_round_trip(semi_implicit::ETD1::new(l63, 0.1));
+    dt_round_trip(semi_implicit::ETDRK2::new(l63, 0.1));
+    dt_round_trip(semi_implicit::ETDRK4::new(l63, 0.1));
+}
+
+#[test]
+fn dt_nstep() {
+    let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.1);
+    let mut teo = adaptor::nstep(teo, 4);
+    assert!((teo.get_dt() - 0.4).abs() < 1e-15);
+    teo.set_dt(0.2);
+    assert!((teo.get_dt() - 0.2).abs() < 1e-15);
+}
+
+#[test]
+fn scheme_order() {
+    let l63 = ode::Lorenz63::default();
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    let o = order(explicit::Euler::new(l63, 1.0), &x0, 0.001, 1000);
+    assert!((o - 1.0).abs() < 0.3, "Euler: {}", o);
+    let o = order(explicit::Heun::new(l63, 1.0), &x0, 0.001, 1000);
+    assert!((o - 2.0).abs() < 0.3, "Heun: {}", o);
+    let o = order(explicit::RK4::new(l63, 1.0), &x0, 0.001, 1000);
+    assert!((o - 4.0).abs() < 0.3, "RK4: {}", o);
+    let o = order(semi_implicit::DiagRK4::new(l63, 1.0), &x0, 0.001, 1000);
+    assert!((o - 4.0).abs() < 0.3, "DiagRK4: {}", o);
+}
+
+fn richardson<Sc>(teo: Sc) -> adaptor::ConvergenceReport<f64>
+where
+    Sc: Scheme<Scalar = f64, Dim = Ix1, Time = f64> + CurrentTime,
+{
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    adaptor::convergence(teo, x0, adaptor::Reference::Richardson, 0.001, 1000, 4)
+}
+
+#[test]
+fn scheme_convergence() {
+    let l63 = ode::Lorenz63::default();
+    richardson(explicit::Euler::new(l63, 1.0)).assert_order(1.0, 0.3);
+    richardson(explicit::Heun::new(l63, 1.0)).assert_order(2.0, 0.3);
+    richardson(explicit::RK4::new(l63, 1.0)).assert_order(4.0, 0.3);
+    let report = richardson(semi_implicit::DiagRK4::new(l63, 1.0));
+    report.assert_order(4.0, 0.3);
+    assert_eq!(report.errors.len(), 4);
+    assert_eq!(report.local_orders.len(), 3);
+}
+
+#[test]
+fn convergence_reference() {
+    let exact = arr1(&[(-1.0_f64).exp()]);
+    let report = adaptor::convergence(
+        explicit::RK4::new(Decay(1), 1.0),
+        arr1(&[1.0]),
+        adaptor::Reference::Solution(exact),
+        0.1,
+        10,
+        4,
+    );
+    report.assert_order(4.0, 0.1);
+    assert!(report.local_orders.iter().all(|o| (o - 4.0).abs() < 0.1));
+}
+
+#[test]
+#[should_panic]
+fn convergence_assert() {
+    let report = adaptor::convergence(
+        explicit::Heun::new(Decay(1), 1.0),
+        arr1(&[1.0]),
+        adaptor::Reference::Solution(arr1(&[(-1.0_f64).exp()])),
+        0.1,
+        10,
+        4,
+    );
+    report.assert_order(4.0, 0.3);
+}
+
+#[test]
+fn convergence_non_autonomous() {
+    // every refinement starts from t = 1
+    let mut teo = explicit::RK4::new(Forced, 1.0);
+    teo.set_time(1.0);
+    let x1 = Forced::solution(1.0, 1.0);
+    let exact = arr1(&[Forced::solution(1.0, 2.0)]);
+    let report = adaptor::convergence(
+        teo,
+        arr1(&[x1]),
+        adaptor::Reference::Solution(exact),
+        0.1,
+        10,
+        4,
+    );
+    report.assert_order(4.0, 0.1);
+}
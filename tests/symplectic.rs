@@ -0,0 +1,87 @@
+use ndarray::*;
+
+use eom::*;
+
+mod common;
+use common::*;
+
+/// Pendulum $H = p^2/2 - \cos q$
+#[derive(Clone, Copy)]
+struct Pendulum;
+
+impl ModelSpec for Pendulum {
+    type Scalar = f64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        2
+    }
+}
+
+impl Hamiltonian for Pendulum {
+    fn energy<S>(&mut self, q: &ArrayBase<S, Ix1>, p: &ArrayBase<S, Ix1>) -> f64
+    where
+        S: Data<Elem = f64>,
+    {
+        0.5 * p[0] * p[0] - q[0].cos()
+    }
+
+    fn dh_dq<'a, S, T>(
+        &mut self,
+        q: &'a mut ArrayBase<S, Ix1>,
+        _p: &ArrayBase<T, Ix1>,
+    ) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+        T: Data<Elem = f64>,
+    {
+        q.mapv_inplace(|q| q.sin());
+        q
+    }
+
+    fn dh_dp<'a, S, T>(
+        &mut self,
+        _q: &ArrayBase<T, Ix1>,
+        p: &'a mut ArrayBase<S, Ix1>,
+    ) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+        T: Data<Elem = f64>,
+    {
+        p
+    }
+}
+
+impl SeparableHamiltonian for Pendulum {}
+
+#[test]
+fn symplectic_order() {
+    let e = 0.3;
+    let x0 = arr1(&[1.0, 0.0]);
+    let o = order(
+        symplectic::SymplecticEuler::new(Pendulum, 1.0),
+        &x0,
+        0.2,
+        10,
+    );
+    assert!((o - 1.0).abs() < e, "SymplecticEuler: {}", o);
+    let o = order(symplectic::Leapfrog::new(Pendulum, 1.0), &x0, 0.2, 10);
+    assert!((o - 2.0).abs() < e, "Leapfrog: {}", o);
+    let o = order(symplectic::ForestRuth::new(Pendulum, 1.0), &x0, 0.2, 10);
+    assert!((o - 4.0).abs() < e, "ForestRuth: {}", o);
+    let o = order(symplectic::Yoshida6::new(Pendulum, 1.0), &x0, 0.2, 10);
+    assert!((o - 6.0).abs() < e, "Yoshida6: {}", o);
+    let o = order(symplectic::Yoshida8::new(Pendulum, 1.0), &x0, 0.2, 10);
+    assert!((o - 8.0).abs() < e, "Yoshida8: {}", o);
+}
+
+#[test]
+fn energy_conservation() {
+    let mut teo = symplectic::Leapfrog::new(Pendulum, 0.1);
+    let mut x = arr1(&[1.0, 0.0]);
+    let e0 = Pendulum.energy(&x.slice(s![..1]), &x.slice(s![1..]));
+    for _ in 0..100_000 {
+        teo.iterate(&mut x);
+    }
+    let e1 = Pendulum.energy(&x.slice(s![..1]), &x.slice(s![1..]));
+    assert!((e1 - e0).abs() < 1e-2);
+}
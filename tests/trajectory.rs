@@ -0,0 +1,141 @@
+use ndarray::*;
+
+use eom::*;
+
+#[test]
+fn timed_series_time() {
+    let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let ts: Vec<_> = adaptor::timed_series(arr1(&[1.0, 0.0, 0.0]), &mut teo)
+        .take(101)
+        .collect();
+    assert_eq!(ts[0].0, 0.0);
+    assert_eq!(ts[0].1, arr1(&[1.0, 0.0, 0.0]));
+    assert!((ts[100].0 - 1.0).abs() < 1e-12);
+    // states are same as time_series
+    let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let xs: Vec<_> = adaptor::time_series(arr1(&[1.0, 0.0, 0.0]), &mut teo)
+        .take(100)
+        .collect();
+    for ((_, x), y) in ts[1..].iter().zip(xs.iter()) {
+        assert_eq!(x, y);
+    }
+}
+
+#[test]
+fn timed_series_nstep() {
+    let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let mut teo = adaptor::nstep(teo, 10);
+    let ts: Vec<_> = adaptor::timed_series(arr1(&[1.0, 0.0, 0.0]), &mut teo)
+        .initial_time(1.0)
+        .take(11)
+        .collect();
+    for (i, (t, _)) in ts.iter().enumerate() {
+        assert!((t - (1.0 + 0.1 * i as f64)).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn timed_series_range() {
+    let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.25);
+    let ts: Vec<_> = adaptor::timed_series(arr1(&[1.0, 0.0, 0.0]), &mut teo)
+        .start(1.0)
+        .end(5.0)
+        .stride(3)
+        .map(|(t, _)| t)
+        .collect();
+    assert_eq!(ts, vec![1.0, 1.75, 2.5, 3.25, 4.0, 4.75]);
+}
+
+#[test]
+fn timed_series_view() {
+    let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let owned: Vec<_> = adaptor::timed_series(arr1(&[1.0, 0.0, 0.0]), &mut teo)
+        .end(1.0)
+        .collect();
+    let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let mut ts = adaptor::timed_series(arr1(&[1.0, 0.0, 0.0]), &mut teo).end(1.0);
+    let mut n = 0;
+    while let Some((t, x)) = ts.next_view() {
+        assert_eq!(t, owned[n].0);
+        assert_eq!(x, owned[n].1);
+        n += 1;
+    }
+    assert_eq!(n, owned.len());
+    assert!(ts.next_view().is_none());
+}
+
+#[test]
+fn timed_series_endpoint() {
+    // 100 steps of 0.01 accumulate to 1.0000000000000007
+    let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let ts: Vec<_> = adaptor::timed_series(arr1(&[1.0, 0.0, 0.0]), &mut teo)
+        .end(1.0)
+        .map(|(t, _)| t)
+        .collect();
+    assert_eq!(ts.len(), 101);
+    assert!((ts[100] - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn timed_series_adaptive() {
+    let mut teo = explicit::DormandPrince45::new(ode::Lorenz63::default(), 0.1);
+    teo.set_tolerance(1e-8, 1e-8);
+    let ts: Vec<_> = adaptor::timed_series(arr1(&[1.0, 0.0, 0.0]), &mut teo)
+        .take(100)
+        .collect();
+    // times of the accepted steps
+    let mut teo = explicit::DormandPrince45::new(ode::Lorenz63::default(), 0.1);
+    teo.set_tolerance(1e-8, 1e-8);
+    let mut x = arr1(&[1.0, 0.0, 0.0]);
+    for (t, y) in ts[1..].iter() {
+        teo.iterate(&mut x);
+        assert_eq!(*t, teo.get_time());
+        assert_eq!(&x, y);
+    }
+}
+
+/// Doubles its step after each iteration
+#[derive(Clone)]
+struct Growing {
+    dt: f64,
+}
+
+impl ModelSpec for Growing {
+    type Scalar = f64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        1
+    }
+}
+
+impl TimeStep for Growing {
+    type Time = f64;
+    fn get_dt(&self) -> f64 {
+        self.dt
+    }
+    fn set_dt(&mut self, dt: f64) {
+        self.dt = dt;
+    }
+}
+
+impl TimeEvolution for Growing {
+    fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        x[0] += self.dt;
+        self.dt *= 2.0;
+        x
+    }
+}
+
+#[test]
+fn timed_step_taken() {
+    let mut teo = adaptor::timed(Growing { dt: 0.1 });
+    let mut x = arr1(&[0.0]);
+    for _ in 0..4 {
+        teo.iterate(&mut x);
+        // x[0] accumulates the steps taken
+        assert!((teo.get_time() - x[0]).abs() < 1e-12);
+    }
+}